@@ -0,0 +1,205 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use core::ops::*;
+
+use crate::Dist;
+
+///A 2d point that can be wrapped in an AABB or transformed.
+///Implemented for both `[T; 2]` and `Vec2<T>`.
+pub trait Point2 {
+    type Num: Copy;
+    fn to_vec2(self) -> Vec2<Self::Num>;
+    fn from_vec2(v: Vec2<Self::Num>) -> Self;
+}
+
+impl<T: Copy> Point2 for [T; 2] {
+    type Num = T;
+    #[inline(always)]
+    fn to_vec2(self) -> Vec2<T> {
+        vec2(self[0], self[1])
+    }
+    #[inline(always)]
+    fn from_vec2(v: Vec2<T>) -> Self {
+        [v.x, v.y]
+    }
+}
+
+impl<T: Copy> Point2 for Vec2<T> {
+    type Num = T;
+    #[inline(always)]
+    fn to_vec2(self) -> Vec2<T> {
+        self
+    }
+    #[inline(always)]
+    fn from_vec2(v: Vec2<T>) -> Self {
+        v
+    }
+}
+
+///Wraps every point of a distribution in an AABB with a constant radius.
+#[derive(Clone)]
+pub struct ConstantAabbAdapter<K, I> {
+    a: I,
+    radius: Vec2<K>,
+}
+
+impl<K, I> ConstantAabbAdapter<K, I> {
+    pub fn new(radius: Vec2<K>, a: I) -> Self {
+        ConstantAabbAdapter { a, radius }
+    }
+}
+
+impl<K, I> Iterator for ConstantAabbAdapter<K, I>
+where
+    K: Add<Output = K> + Sub<Output = K> + Copy,
+    I: Iterator,
+    I::Item: Point2<Num = K>,
+{
+    type Item = Rect<K>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = self.radius;
+        self.a.next().map(|a| Rect::from_point(a.to_vec2(), r))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.a.size_hint()
+    }
+}
+
+impl<K, I> FusedIterator for ConstantAabbAdapter<K, I>
+where
+    K: Add<Output = K> + Sub<Output = K> + Copy,
+    I: FusedIterator,
+    I::Item: Point2<Num = K>,
+{
+}
+
+impl<K, I> ExactSizeIterator for ConstantAabbAdapter<K, I>
+where
+    K: Add<Output = K> + Sub<Output = K> + Copy,
+    I: ExactSizeIterator,
+    I::Item: Point2<Num = K>,
+{
+}
+
+impl<K, I> Dist for ConstantAabbAdapter<K, I>
+where
+    K: Add<Output = K> + Sub<Output = K> + Copy,
+    I: Dist<Num = K>,
+    I::Item: Point2<Num = K>,
+{
+    type Num = K;
+    fn exact_len(&self) -> Option<usize> {
+        self.a.exact_len()
+    }
+    fn bounding_rect(&self) -> Option<Rect<K>> {
+        let r = self.radius;
+        self.a.bounding_rect().map(|a| {
+            Rect::new(
+                a.x.start - r.x,
+                a.x.end + r.x,
+                a.y.start - r.y,
+                a.y.end + r.y,
+            )
+        })
+    }
+}
+
+///Wraps every point of a distribution in an AABB
+///whose radius is taken from a second iterator.
+///Ends as soon as either iterator ends.
+#[derive(Clone)]
+pub struct RadiusAabbAdapter<I, R> {
+    a: I,
+    radius: R,
+}
+
+impl<I, R> RadiusAabbAdapter<I, R> {
+    pub fn new(radius: R, a: I) -> Self {
+        RadiusAabbAdapter { a, radius }
+    }
+}
+
+impl<K, I, R> Iterator for RadiusAabbAdapter<I, R>
+where
+    K: Add<Output = K> + Sub<Output = K> + Copy,
+    I: Iterator,
+    I::Item: Point2<Num = K>,
+    R: Iterator<Item = K>,
+{
+    type Item = Rect<K>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = self.a.next()?;
+        let r = self.radius.next()?;
+        Some(Rect::from_point(a.to_vec2(), vec2same(r)))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a_min, a_max) = self.a.size_hint();
+        let (r_min, r_max) = self.radius.size_hint();
+        let max = match (a_max, r_max) {
+            (Some(a), Some(r)) => Some(a.min(r)),
+            (a, None) => a,
+            (None, r) => r,
+        };
+        (a_min.min(r_min), max)
+    }
+}
+
+impl<K, I, R> FusedIterator for RadiusAabbAdapter<I, R>
+where
+    K: Add<Output = K> + Sub<Output = K> + Copy,
+    I: FusedIterator,
+    I::Item: Point2<Num = K>,
+    R: FusedIterator<Item = K>,
+{
+}
+
+impl<K, I, R> Dist for RadiusAabbAdapter<I, R>
+where
+    K: Add<Output = K> + Sub<Output = K> + Copy,
+    I: Dist<Num = K>,
+    I::Item: Point2<Num = K>,
+    R: Iterator<Item = K>,
+{
+    type Num = K;
+    fn exact_len(&self) -> Option<usize> {
+        self.a
+            .exact_len()
+            .filter(|&len| self.radius.size_hint().0 >= len)
+    }
+    fn bounding_rect(&self) -> Option<Rect<K>> {
+        None
+    }
+}
+
+///Extension methods to turn any point distribution into AABBs.
+pub trait AabbExt: Iterator + Sized
+where
+    Self::Item: Point2,
+{
+    ///Wrap every point in a square AABB with the given radius.
+    fn aabbs(
+        self,
+        radius: <Self::Item as Point2>::Num,
+    ) -> ConstantAabbAdapter<<Self::Item as Point2>::Num, Self> {
+        ConstantAabbAdapter::new(vec2same(radius), self)
+    }
+
+    ///Wrap every point in an AABB with a different radius along each axis.
+    fn aabbs_xy(
+        self,
+        radius: Vec2<<Self::Item as Point2>::Num>,
+    ) -> ConstantAabbAdapter<<Self::Item as Point2>::Num, Self> {
+        ConstantAabbAdapter::new(radius, self)
+    }
+
+    ///Wrap every point in a square AABB whose radius is taken from `radius`,
+    ///for example [`rand_iter`](crate::rand_iter).
+    fn aabbs_from<R: Iterator<Item = <Self::Item as Point2>::Num>>(
+        self,
+        radius: R,
+    ) -> RadiusAabbAdapter<Self, R> {
+        RadiusAabbAdapter::new(radius, self)
+    }
+}
+
+impl<I: Iterator> AabbExt for I where I::Item: Point2 {}
@@ -0,0 +1,353 @@
+use axgeom::*;
+
+use crate::density::DensityError;
+use crate::density::DensityGrid;
+
+///Why a bitmap could not be created.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BitmapError {
+    ///The image has no pixels.
+    NoPixels,
+    ///The number of pixels does not match the dimensions.
+    WrongLength,
+    ///The data is not a valid PGM image.
+    InvalidPgm,
+    ///The data is not a valid PNG image.
+    InvalidPng,
+}
+
+impl core::fmt::Display for BitmapError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            BitmapError::NoPixels => write!(f, "the image has no pixels"),
+            BitmapError::WrongLength => {
+                write!(f, "the number of pixels does not match the dimensions")
+            }
+            BitmapError::InvalidPgm => write!(f, "the data is not a valid PGM image"),
+            BitmapError::InvalidPng => write!(f, "the data is not a valid PNG image"),
+        }
+    }
+}
+
+impl std::error::Error for BitmapError {}
+
+///An 8 bit grayscale image.
+///
+///Rows are stored top to bottom. When mapped onto a rect, the first row
+///lies along `rect.y.start` and the first column along `rect.x.start`.
+///
+///Use [`Bitmap::density`] or [`Bitmap::mask`] to turn it into a [`DensityGrid`],
+///then sample it with [`crate::density::density_iter`] for random points or with
+///[`crate::density::density_stratified_iter`] for stratified points.
+#[derive(Clone, Debug)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    ///Create a bitmap from one intensity per pixel in row major order.
+    pub fn new(width: usize, height: usize, pixels: Vec<u8>) -> Result<Bitmap, BitmapError> {
+        if width == 0 || height == 0 {
+            return Err(BitmapError::NoPixels);
+        }
+        if pixels.len() != width * height {
+            return Err(BitmapError::WrongLength);
+        }
+        Ok(Bitmap {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    ///Parse a binary (P5) or plain (P2) PGM image.
+    ///Images with a maximum value other than 255 are rescaled to 8 bits.
+    pub fn from_pgm(bytes: &[u8]) -> Result<Bitmap, BitmapError> {
+        let mut parser = PgmParser { bytes, pos: 0 };
+
+        let binary = match parser.token()? {
+            b"P5" => true,
+            b"P2" => false,
+            _ => return Err(BitmapError::InvalidPgm),
+        };
+        let width = parser.number()?;
+        let height = parser.number()?;
+        let maxval = parser.number()?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(BitmapError::InvalidPgm);
+        }
+        let num = width.checked_mul(height).ok_or(BitmapError::InvalidPgm)?;
+
+        let rescale = |v: usize| -> Result<u8, BitmapError> {
+            if v > maxval {
+                return Err(BitmapError::InvalidPgm);
+            }
+            Ok(((v * 255 + maxval / 2) / maxval) as u8)
+        };
+
+        let pixels = if binary {
+            //Exactly one whitespace character separates the header from the data.
+            parser.pos += 1;
+            let bytes_per = if maxval < 256 { 1 } else { 2 };
+            let data = parser
+                .bytes
+                .get(parser.pos..)
+                .and_then(|d| d.get(..num.checked_mul(bytes_per)?))
+                .ok_or(BitmapError::InvalidPgm)?;
+            data.chunks_exact(bytes_per)
+                .map(|c| rescale(c.iter().fold(0, |acc, &b| acc << 8 | b as usize)))
+                .collect::<Result<Vec<u8>, _>>()?
+        } else {
+            (0..num)
+                .map(|_| rescale(parser.number()?))
+                .collect::<Result<Vec<u8>, _>>()?
+        };
+
+        Bitmap::new(width, height, pixels)
+    }
+
+    ///Decode a PNG image, converting it to grayscale.
+    ///Color is converted using its luminance, and transparent pixels are treated as black.
+    #[cfg(feature = "png")]
+    pub fn from_png(bytes: &[u8]) -> Result<Bitmap, BitmapError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|_| BitmapError::InvalidPng)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buf)
+            .map_err(|_| BitmapError::InvalidPng)?;
+
+        let channels = info.color_type.samples();
+        let (width, height) = (info.width as usize, info.height as usize);
+
+        let luma = |p: &[u8]| -> u8 {
+            let (gray, alpha) = match p {
+                [g] => (*g as u32, 255),
+                [g, a] => (*g as u32, *a as u32),
+                [r, g, b] => (
+                    (299 * *r as u32 + 587 * *g as u32 + 114 * *b as u32) / 1000,
+                    255,
+                ),
+                [r, g, b, a] => (
+                    (299 * *r as u32 + 587 * *g as u32 + 114 * *b as u32) / 1000,
+                    *a as u32,
+                ),
+                _ => unreachable!(),
+            };
+            (gray * alpha / 255) as u8
+        };
+
+        let pixels = buf[..info.buffer_size()]
+            .chunks_exact(info.line_size)
+            .flat_map(|line| line[..width * channels].chunks_exact(channels).map(luma))
+            .collect();
+
+        Bitmap::new(width, height, pixels)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    ///The intensities of every pixel in row major order.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    ///Map the bitmap onto the rect, using the intensity of every pixel as its density.
+    ///Black pixels never receive points.
+    pub fn density(&self, rect: Rect<f32>) -> Result<DensityGrid, DensityError> {
+        let values: Vec<f32> = self.pixels.iter().map(|&p| p as f32 / 255.0).collect();
+        DensityGrid::new(rect, [self.width, self.height], &values)
+    }
+
+    ///Map the bitmap onto the rect as a binary mask.
+    ///Points are spread evenly over every pixel at least as bright as the threshold.
+    pub fn mask(&self, rect: Rect<f32>, threshold: u8) -> Result<DensityGrid, DensityError> {
+        let values: Vec<f32> = self
+            .pixels
+            .iter()
+            .map(|&p| if p >= threshold { 1.0 } else { 0.0 })
+            .collect();
+        DensityGrid::new(rect, [self.width, self.height], &values)
+    }
+}
+
+struct PgmParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PgmParser<'a> {
+    ///Skip whitespace and comments, then return the next run of non whitespace.
+    fn token(&mut self) -> Result<&'a [u8], BitmapError> {
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                Some(b'#') => {
+                    while !matches!(self.bytes.get(self.pos), None | Some(b'\n') | Some(b'\r')) {
+                        self.pos += 1;
+                    }
+                }
+                Some(_) => break,
+                None => return Err(BitmapError::InvalidPgm),
+            }
+        }
+        let start = self.pos;
+        while matches!(self.bytes.get(self.pos), Some(b) if !b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        Ok(&self.bytes[start..self.pos])
+    }
+
+    fn number(&mut self) -> Result<usize, BitmapError> {
+        core::str::from_utf8(self.token()?)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(BitmapError::InvalidPgm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_pgm_with_comments() {
+        let data = b"P2\n# made by hand\n3 # width\n2\n15\n0 15 0\n# second row\n 0 0 5\n";
+        let bitmap = Bitmap::from_pgm(data).unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (3, 2));
+        assert_eq!(bitmap.pixels(), [0, 255, 0, 0, 0, 85]);
+    }
+
+    #[test]
+    fn binary_pgm_one_byte() {
+        let mut data = b"P5 2 2 255\n".to_vec();
+        data.extend([0, 10, 128, 255]);
+        assert_eq!(Bitmap::from_pgm(&data).unwrap().pixels(), [0, 10, 128, 255]);
+
+        //A maxval below 255 is rescaled.
+        let mut data = b"P5 2 1 127\n".to_vec();
+        data.extend([127, 64]);
+        assert_eq!(Bitmap::from_pgm(&data).unwrap().pixels(), [255, 129]);
+    }
+
+    #[test]
+    fn binary_pgm_two_bytes() {
+        //Big endian samples.
+        let mut data = b"P5 2 2 65535\n".to_vec();
+        data.extend([0, 0, 255, 255, 128, 0, 0, 1]);
+        assert_eq!(Bitmap::from_pgm(&data).unwrap().pixels(), [0, 255, 128, 0]);
+
+        let mut data = b"P5 1 1 1000\n".to_vec();
+        data.extend(500u16.to_be_bytes());
+        assert_eq!(Bitmap::from_pgm(&data).unwrap().pixels(), [128]);
+    }
+
+    #[test]
+    fn truncated_pgm() {
+        let mut data = b"P5 2 2 255\n".to_vec();
+        data.extend([0, 1, 2]);
+        assert_eq!(Bitmap::from_pgm(&data).err(), Some(BitmapError::InvalidPgm));
+
+        let mut data = b"P5 2 1 1000\n".to_vec();
+        data.extend([0, 1, 2]);
+        assert_eq!(Bitmap::from_pgm(&data).err(), Some(BitmapError::InvalidPgm));
+
+        for data in [&b"P2 2 2 255\n1 2 3"[..], b"P2 2 2", b"P5", b""] {
+            assert_eq!(Bitmap::from_pgm(data).err(), Some(BitmapError::InvalidPgm));
+        }
+    }
+
+    #[test]
+    fn pgm_value_above_maxval() {
+        assert_eq!(
+            Bitmap::from_pgm(b"P2 2 1 15\n3 16\n").err(),
+            Some(BitmapError::InvalidPgm)
+        );
+
+        let mut data = b"P5 1 1 1000\n".to_vec();
+        data.extend(1001u16.to_be_bytes());
+        assert_eq!(Bitmap::from_pgm(&data).err(), Some(BitmapError::InvalidPgm));
+
+        let mut data = b"P5 1 1 100\n".to_vec();
+        data.push(101);
+        assert_eq!(Bitmap::from_pgm(&data).err(), Some(BitmapError::InvalidPgm));
+    }
+
+    #[test]
+    fn bad_pgm_header() {
+        for data in [
+            &b"P6 1 1 255\n\0\0\0"[..],
+            b"P3 1 1 255\n0 0 0\n",
+            b"not an image",
+            b"P2 x 1 255\n0\n",
+            b"P2 1 1 0\n0\n",
+            b"P2 1 1 65536\n0\n",
+        ] {
+            assert_eq!(Bitmap::from_pgm(data).err(), Some(BitmapError::InvalidPgm));
+        }
+        assert_eq!(
+            Bitmap::from_pgm(b"P2 0 1 255\n").err(),
+            Some(BitmapError::NoPixels)
+        );
+    }
+
+    #[test]
+    fn mask_maps_rows_onto_rect() {
+        let bitmap = Bitmap::new(2, 2, vec![255, 0, 0, 10]).unwrap();
+        let grid = bitmap.mask(rect(0.0, 2.0, 0.0, 2.0), 128).unwrap();
+        let points: Vec<[f32; 2]> = crate::density::density_iter_seeded(&grid, 1)
+            .take(1000)
+            .collect();
+        assert!(points.iter().all(|p| p[0] < 1.0 && p[1] < 1.0));
+
+        assert_eq!(
+            Bitmap::new(2, 2, vec![0; 3]).err(),
+            Some(BitmapError::WrongLength)
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png() {
+        //A 3x1 RGBA image of opaque white, half transparent red and opaque black.
+        const RGBA: [u8; 81] = [
+            137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 3, 0, 0, 0, 1,
+            8, 6, 0, 0, 0, 27, 224, 20, 180, 0, 0, 0, 24, 73, 68, 65, 84, 120, 1, 1, 13, 0, 242,
+            255, 0, 255, 255, 255, 255, 255, 0, 0, 128, 0, 0, 0, 255, 53, 90, 6, 123, 241, 121,
+            146, 34, 0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130,
+        ];
+        //A 2x2 grayscale image.
+        const GRAY: [u8; 74] = [
+            137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 2, 0, 0, 0, 2,
+            8, 0, 0, 0, 0, 87, 221, 82, 248, 0, 0, 0, 17, 73, 68, 65, 84, 120, 1, 1, 6, 0, 249,
+            255, 0, 0, 200, 0, 50, 255, 4, 137, 1, 250, 208, 62, 185, 109, 0, 0, 0, 0, 73, 69, 78,
+            68, 174, 66, 96, 130,
+        ];
+
+        let bitmap = Bitmap::from_png(&RGBA).unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (3, 1));
+        assert_eq!(bitmap.pixels(), [255, 38, 0]);
+
+        let bitmap = Bitmap::from_png(&GRAY).unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (2, 2));
+        assert_eq!(bitmap.pixels(), [0, 200, 50, 255]);
+
+        assert_eq!(
+            Bitmap::from_png(&RGBA[..40]).err(),
+            Some(BitmapError::InvalidPng)
+        );
+        assert_eq!(
+            Bitmap::from_png(b"P5 1 1 255\n\0").err(),
+            Some(BitmapError::InvalidPng)
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use rand::RngCore;
+
+use crate::coord::*;
+use crate::gaussian::standard_normal;
+use crate::rand_between;
+use crate::seeded_rng;
+use crate::unit_f32;
+use crate::Dist;
+
+///Draw from a poisson distribution with the given mean.
+///Large means are split into chunks so that `exp(-mean)` does not underflow.
+fn poisson_count<R: RngCore>(mut mean: f64, rng: &mut R) -> usize {
+    const CHUNK: f64 = 32.0;
+
+    let mut count = 0;
+    while mean > 0.0 {
+        let m = mean.min(CHUNK);
+        mean -= m;
+
+        //Knuth's multiplication method.
+        let limit = (-m).exp();
+        let mut p = 1.0 - unit_f32(rng) as f64;
+        while p > limit {
+            count += 1;
+            p *= 1.0 - unit_f32(rng) as f64;
+        }
+    }
+    count
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Scatter {
+    Thomas { sigma: f64 },
+    Matern { radius: f64 },
+}
+
+impl Scatter {
+    ///How far outside of the rect a parent can be and still likely have children inside of it.
+    fn reach(&self) -> f64 {
+        match *self {
+            Scatter::Thomas { sigma } => 4.0 * sigma,
+            Scatter::Matern { radius } => radius,
+        }
+    }
+
+    fn offset<R: RngCore>(&self, rng: &mut R) -> [f64; 2] {
+        match *self {
+            Scatter::Thomas { sigma } => {
+                let [x, y] = standard_normal(rng);
+                [sigma * x, sigma * y]
+            }
+            Scatter::Matern { radius } => {
+                let r = radius * (unit_f32(rng) as f64).sqrt();
+                let (s, c) = (core::f64::consts::TAU * unit_f32(rng) as f64).sin_cos();
+                [r * c, r * s]
+            }
+        }
+    }
+}
+
+///One realization of a clustered point process.
+#[derive(Clone)]
+struct ClusterProcess<T, R> {
+    bounds: Rect<T>,
+    rect: Rect<f64>,
+    parent_rect: Rect<f64>,
+    scatter: Scatter,
+    mean_children: f64,
+    parents_left: usize,
+    parent: [f64; 2],
+    children_left: usize,
+    rng: R,
+}
+
+impl<T: Coord, R: RngCore> ClusterProcess<T, R> {
+    fn new(
+        bounds: Rect<T>,
+        parent_intensity: f64,
+        mean_children: f64,
+        scatter: Scatter,
+        mut rng: R,
+    ) -> Self {
+        assert!(
+            parent_intensity >= 0.0 && mean_children >= 0.0,
+            "intensities must not be negative"
+        );
+
+        let rect = rect_to_f64(bounds);
+
+        //Parents are placed in a larger rect, so that clusters
+        //near the border are not thinned out.
+        let reach = scatter.reach();
+        let parent_rect = Rect::new(
+            rect.x.start - reach,
+            rect.x.end + reach,
+            rect.y.start - reach,
+            rect.y.end + reach,
+        );
+        let area = (parent_rect.x.end - parent_rect.x.start).max(0.0)
+            * (parent_rect.y.end - parent_rect.y.start).max(0.0);
+        let parents_left = poisson_count(parent_intensity * area, &mut rng);
+
+        ClusterProcess {
+            bounds,
+            rect,
+            parent_rect,
+            scatter,
+            mean_children,
+            parents_left,
+            parent: [0.0; 2],
+            children_left: 0,
+            rng,
+        }
+    }
+}
+
+impl<T: Coord, R: RngCore> Iterator for ClusterProcess<T, R> {
+    type Item = [T; 2];
+    fn next(&mut self) -> Option<[T; 2]> {
+        loop {
+            if self.children_left > 0 {
+                self.children_left -= 1;
+                let [ox, oy] = self.scatter.offset(&mut self.rng);
+                let [x, y] = [self.parent[0] + ox, self.parent[1] + oy];
+
+                //Children outside of the rect are discarded.
+                if x >= self.rect.x.start
+                    && x < self.rect.x.end
+                    && y >= self.rect.y.start
+                    && y < self.rect.y.end
+                {
+                    return Some(point_from_f64([x, y]));
+                }
+                continue;
+            }
+
+            if self.parents_left == 0 {
+                return None;
+            }
+            self.parents_left -= 1;
+
+            let r = self.parent_rect;
+            self.parent = [
+                rand_between(r.x.start, r.x.end, &mut self.rng),
+                rand_between(r.y.start, r.y.end, &mut self.rng),
+            ];
+            self.children_left = poisson_count(self.mean_children, &mut self.rng);
+        }
+    }
+}
+
+impl<T: Coord, R: RngCore> FusedIterator for ClusterProcess<T, R> {}
+
+impl<T: Coord, R: RngCore> Dist for ClusterProcess<T, R> {
+    type Num = T;
+    fn exact_len(&self) -> Option<usize> {
+        None
+    }
+    fn bounding_rect(&self) -> Option<Rect<T>> {
+        Some(self.bounds)
+    }
+}
+
+///A thomas cluster process over the rect.
+///
+///Parents are placed uniformly with `parent_intensity` parents per unit area.
+///Each parent has a poisson distributed number of children with mean `mean_children`,
+///scattered around it with a gaussian of standard deviation `sigma`.
+///Only the children inside of the rect are yielded.
+pub fn thomas_iter<T: Coord>(
+    rect: Rect<T>,
+    parent_intensity: f64,
+    mean_children: f64,
+    sigma: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    thomas_iter_from_rng(
+        rect,
+        parent_intensity,
+        mean_children,
+        sigma,
+        rand::thread_rng(),
+    )
+}
+
+///Same as [`thomas_iter`] but reproducible from a seed.
+pub fn thomas_iter_seeded<T: Coord>(
+    rect: Rect<T>,
+    parent_intensity: f64,
+    mean_children: f64,
+    sigma: f64,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    thomas_iter_from_rng(
+        rect,
+        parent_intensity,
+        mean_children,
+        sigma,
+        seeded_rng(seed),
+    )
+}
+
+///Same as [`thomas_iter`] but draws from the provided rng.
+pub fn thomas_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    rect: Rect<T>,
+    parent_intensity: f64,
+    mean_children: f64,
+    sigma: f64,
+    rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    assert!(sigma >= 0.0, "sigma must not be negative");
+    let scatter = Scatter::Thomas { sigma };
+    ClusterProcess::new(rect, parent_intensity, mean_children, scatter, rng)
+}
+
+///A matérn cluster process over the rect.
+///
+///Same as [`thomas_iter`] except that the children are placed
+///uniformly in a disk of the given `radius` around their parent.
+pub fn matern_iter<T: Coord>(
+    rect: Rect<T>,
+    parent_intensity: f64,
+    mean_children: f64,
+    radius: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    matern_iter_from_rng(
+        rect,
+        parent_intensity,
+        mean_children,
+        radius,
+        rand::thread_rng(),
+    )
+}
+
+///Same as [`matern_iter`] but reproducible from a seed.
+pub fn matern_iter_seeded<T: Coord>(
+    rect: Rect<T>,
+    parent_intensity: f64,
+    mean_children: f64,
+    radius: f64,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    matern_iter_from_rng(
+        rect,
+        parent_intensity,
+        mean_children,
+        radius,
+        seeded_rng(seed),
+    )
+}
+
+///Same as [`matern_iter`] but draws from the provided rng.
+pub fn matern_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    rect: Rect<T>,
+    parent_intensity: f64,
+    mean_children: f64,
+    radius: f64,
+    rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    assert!(radius >= 0.0, "radius must not be negative");
+    let scatter = Scatter::Matern { radius };
+    ClusterProcess::new(rect, parent_intensity, mean_children, scatter, rng)
+}
@@ -0,0 +1,243 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::Dist;
+
+///A coordinate type that every distribution can produce.
+///Implemented for `f32`, `f64`, `i32`, `i64` and `isize`.
+///
+///All conversions go through `f64`.
+///Conversions to integers round down, so points stay inside of their bounding rect.
+///Unlike an `as` cast this does not truncate towards zero, so `-0.5` becomes `-1`.
+pub trait Coord: Copy + PartialOrd + core::fmt::Debug + Send + Sync + 'static {
+    fn to_f64(self) -> f64;
+    fn from_f64(a: f64) -> Self;
+
+    ///Linearly interpolate between `a` and `b`.
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        let a = a.to_f64();
+        Self::from_f64(a + t * (b.to_f64() - a))
+    }
+
+    fn cast<T: Coord>(self) -> T {
+        T::from_f64(self.to_f64())
+    }
+}
+
+impl Coord for f32 {
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    #[inline(always)]
+    fn from_f64(a: f64) -> Self {
+        a as f32
+    }
+    //Interpolate in f32 so that results do not depend on the f64 round trip.
+    #[inline(always)]
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        a + t as f32 * (b - a)
+    }
+}
+
+impl Coord for f64 {
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self
+    }
+    #[inline(always)]
+    fn from_f64(a: f64) -> Self {
+        a
+    }
+}
+
+macro_rules! impl_coord_int {
+    ($($t:ty),*) => {
+        $(
+            impl Coord for $t {
+                #[inline(always)]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+                #[inline(always)]
+                fn from_f64(a: f64) -> Self {
+                    a.floor() as $t
+                }
+            }
+        )*
+    };
+}
+impl_coord_int!(i32, i64, isize);
+
+pub(crate) fn point_to_f64<T: Coord>(p: [T; 2]) -> [f64; 2] {
+    [p[0].to_f64(), p[1].to_f64()]
+}
+
+pub(crate) fn point_from_f64<T: Coord>(p: [f64; 2]) -> [T; 2] {
+    [T::from_f64(p[0]), T::from_f64(p[1])]
+}
+
+pub(crate) fn rect_to_f64<T: Coord>(r: Rect<T>) -> Rect<f64> {
+    r.cast_coord()
+}
+
+///An item whose coordinates can be converted to another [`Coord`] type.
+pub trait CastCoord<T> {
+    type Output;
+    fn cast_coord(self) -> Self::Output;
+}
+
+impl<K: Coord, T: Coord> CastCoord<T> for [K; 2] {
+    type Output = [T; 2];
+    fn cast_coord(self) -> [T; 2] {
+        [self[0].cast(), self[1].cast()]
+    }
+}
+
+impl<K: Coord, T: Coord> CastCoord<T> for Vec2<K> {
+    type Output = Vec2<T>;
+    fn cast_coord(self) -> Vec2<T> {
+        vec2(self.x.cast(), self.y.cast())
+    }
+}
+
+impl<K: Coord, T: Coord> CastCoord<T> for Rect<K> {
+    type Output = Rect<T>;
+    fn cast_coord(self) -> Rect<T> {
+        Rect::new(
+            self.x.start.cast(),
+            self.x.end.cast(),
+            self.y.start.cast(),
+            self.y.end.cast(),
+        )
+    }
+}
+
+///Converts the coordinates of every item of a distribution.
+pub struct Cast<I, T> {
+    inner: I,
+    _p: PhantomData<fn() -> T>,
+}
+
+impl<I: Clone, T> Clone for Cast<I, T> {
+    fn clone(&self) -> Self {
+        Cast {
+            inner: self.inner.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<I: Iterator, T> Iterator for Cast<I, T>
+where
+    I::Item: CastCoord<T>,
+{
+    type Item = <I::Item as CastCoord<T>>::Output;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(CastCoord::cast_coord)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I: DoubleEndedIterator, T> DoubleEndedIterator for Cast<I, T>
+where
+    I::Item: CastCoord<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(CastCoord::cast_coord)
+    }
+}
+
+impl<I: ExactSizeIterator, T> ExactSizeIterator for Cast<I, T> where I::Item: CastCoord<T> {}
+
+impl<I: FusedIterator, T> FusedIterator for Cast<I, T> where I::Item: CastCoord<T> {}
+
+impl<I: Dist, T: Coord> Dist for Cast<I, T>
+where
+    I::Item: CastCoord<T>,
+    I::Num: Coord,
+{
+    type Num = T;
+    fn exact_len(&self) -> Option<usize> {
+        self.inner.exact_len()
+    }
+    fn bounding_rect(&self) -> Option<Rect<T>> {
+        self.inner.bounding_rect().map(CastCoord::cast_coord)
+    }
+}
+
+///Extension method to convert the coordinates of any distribution.
+pub trait CastExt: Iterator + Sized {
+    ///Convert every item to use `T` coordinates, for example `dist.cast::<isize>()`.
+    fn cast<T>(self) -> Cast<Self, T>
+    where
+        Self::Item: CastCoord<T>,
+    {
+        Cast {
+            inner: self,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<I: Iterator> CastExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_f64_rounds_down() {
+        assert_eq!(i32::from_f64(2.7), 2);
+        assert_eq!(i32::from_f64(-0.2), -1);
+        assert_eq!(i32::from_f64(-2.7), -3);
+        assert_eq!(i32::from_f64(-3.0), -3);
+
+        assert_eq!(i64::from_f64(-0.5), -1);
+        assert_eq!(i64::from_f64(5e12 + 0.5), 5_000_000_000_000);
+        assert_eq!(isize::from_f64(-1.5), -2);
+        assert_eq!(isize::from_f64(7.9), 7);
+
+        assert_eq!(f32::from_f64(-0.25), -0.25);
+        assert_eq!((-1.5f32).cast::<i64>(), -2);
+    }
+
+    #[test]
+    fn lerp_stays_in_range() {
+        for &(a, b) in &[(0, 10), (-10, -3), (-5, 5)] {
+            for i in 0..1000 {
+                let t = i as f64 / 1000.0;
+                let v = i32::lerp(a, b, t);
+                assert!(a <= v && v < b, "{} not in [{}, {})", v, a, b);
+            }
+            assert_eq!(i32::lerp(a, b, 0.0), a);
+            assert_eq!(i32::lerp(a, b, 0.999_999), b - 1);
+            assert_eq!(
+                isize::lerp(a as isize, b as isize, 0.999_999),
+                b as isize - 1
+            );
+        }
+    }
+
+    #[test]
+    fn cast_keeps_len_and_rect() {
+        let rect = Rect::new(-2.5f64, 7.5, -10.0, 0.5);
+        let it = crate::grid_rect_iter(12, rect).cast::<i32>();
+        assert_eq!(it.len(), 12);
+        assert_eq!(it.exact_len(), Some(12));
+        assert_eq!(it.bounding_rect(), Some(Rect::new(-3, 7, -10, 0)));
+
+        let points: Vec<[i32; 2]> = it.clone().collect();
+        let mut backward: Vec<[i32; 2]> = it.rev().collect();
+        backward.reverse();
+        assert_eq!(points, backward);
+
+        let expected: Vec<[i32; 2]> = crate::grid_rect_iter(12, rect)
+            .map(|[x, y]: [f64; 2]| [x.floor() as i32, y.floor() as i32])
+            .collect();
+        assert_eq!(points, expected);
+    }
+}
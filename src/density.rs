@@ -0,0 +1,363 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use rand::RngCore;
+use std::sync::Arc;
+
+use crate::coord::*;
+use crate::seeded_rng;
+use crate::unit_f32;
+use crate::Dist;
+use crate::DistIter;
+
+///Why a density grid could not be created.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DensityError {
+    ///The grid has no cells.
+    NoCells,
+    ///The number of values does not match the number of cells.
+    WrongLength,
+    ///A density is negative or not finite.
+    InvalidDensity,
+    ///Every density is zero.
+    ZeroTotal,
+}
+
+impl core::fmt::Display for DensityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DensityError::NoCells => write!(f, "the grid has no cells"),
+            DensityError::WrongLength => {
+                write!(f, "the number of values does not match the number of cells")
+            }
+            DensityError::InvalidDensity => write!(f, "a density is negative or not finite"),
+            DensityError::ZeroTotal => write!(f, "every density is zero"),
+        }
+    }
+}
+
+impl std::error::Error for DensityError {}
+
+///Walker's alias method, built using Vose's algorithm.
+///Picks an index with probability proportional to its weight in constant time.
+#[derive(Clone, Debug)]
+struct AliasTable {
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    fn new(weights: &[f64]) -> AliasTable {
+        let n = weights.len();
+        let total: f64 = weights.iter().sum();
+
+        let mut prob: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut alias: Vec<usize> = (0..n).collect();
+
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..n).partition(|&i| prob[i] < 1.0);
+
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            alias[s] = l;
+            prob[l] -= 1.0 - prob[s];
+            if prob[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+
+        //Anything left over is only there because of rounding errors.
+        for i in small.into_iter().chain(large) {
+            prob[i] = 1.0;
+        }
+
+        AliasTable { prob, alias }
+    }
+
+    fn sample<R: RngCore>(&self, rng: &mut R) -> usize {
+        let i = ((rng.next_u32() as u64 * self.prob.len() as u64) >> 32) as usize;
+        if (unit_f32(rng) as f64) < self.prob[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+///A rect split into a grid of cells, each with a density.
+///Points are sampled by picking a cell in proportion to its density
+///and then placing the point uniformly within that cell.
+#[derive(Clone, Debug)]
+pub struct DensityGrid {
+    rect: Rect<f32>,
+    dim: [usize; 2],
+    table: AliasTable,
+    cdf: Vec<f64>,
+}
+
+impl DensityGrid {
+    ///Create a density grid from one value per cell in row major order.
+    pub fn new(
+        rect: Rect<f32>,
+        dim: [usize; 2],
+        values: &[f32],
+    ) -> Result<DensityGrid, DensityError> {
+        if dim[0] == 0 || dim[1] == 0 {
+            return Err(DensityError::NoCells);
+        }
+        if values.len() != dim[0] * dim[1] {
+            return Err(DensityError::WrongLength);
+        }
+        if values.iter().any(|v| !(v.is_finite() && *v >= 0.0)) {
+            return Err(DensityError::InvalidDensity);
+        }
+        let weights: Vec<f64> = values.iter().map(|&v| v as f64).collect();
+        if weights.iter().sum::<f64>() <= 0.0 {
+            return Err(DensityError::ZeroTotal);
+        }
+
+        let total: f64 = weights.iter().sum();
+        let mut acc = 0.0;
+        let mut cdf: Vec<f64> = weights
+            .iter()
+            .map(|w| {
+                acc += w;
+                acc / total
+            })
+            .collect();
+        *cdf.last_mut().unwrap() = 1.0;
+
+        Ok(DensityGrid {
+            rect,
+            dim,
+            table: AliasTable::new(&weights),
+            cdf,
+        })
+    }
+
+    ///Create a density grid by evaluating the density function at the center of every cell.
+    ///The finer the grid, the more closely the samples follow the function.
+    pub fn from_fn(
+        rect: Rect<f32>,
+        dim: [usize; 2],
+        func: impl Fn([f32; 2]) -> f32,
+    ) -> Result<DensityGrid, DensityError> {
+        let cell = cell_size(rect, dim);
+        let values: Vec<f32> = crate::grid_iter(dim)
+            .map(|[x, y]| {
+                func([
+                    rect.x.start + (x as f32 + 0.5) * cell[0],
+                    rect.y.start + (y as f32 + 0.5) * cell[1],
+                ])
+            })
+            .collect();
+        DensityGrid::new(rect, dim, &values)
+    }
+
+    pub fn rect(&self) -> Rect<f32> {
+        self.rect
+    }
+
+    pub fn dim(&self) -> [usize; 2] {
+        self.dim
+    }
+
+    ///Sample one point.
+    pub fn sample<R: RngCore>(&self, rng: &mut R) -> [f64; 2] {
+        let i = self.table.sample(rng);
+        self.point_in_cell(i, rng)
+    }
+
+    ///Find the cell that a value in `[0,1)` falls on when the cells
+    ///are laid end to end in proportion to their density.
+    fn invert_cdf(&self, u: f64) -> usize {
+        self.cdf
+            .partition_point(|&c| c <= u)
+            .min(self.cdf.len() - 1)
+    }
+
+    fn point_in_cell<R: RngCore>(&self, i: usize, rng: &mut R) -> [f64; 2] {
+        let [x, y] = [i % self.dim[0], i / self.dim[0]];
+        let rect = rect_to_f64(self.rect);
+        let cell = [
+            (rect.x.end - rect.x.start) / self.dim[0] as f64,
+            (rect.y.end - rect.y.start) / self.dim[1] as f64,
+        ];
+        [
+            rect.x.start + (x as f64 + unit_f32(rng) as f64) * cell[0],
+            rect.y.start + (y as f64 + unit_f32(rng) as f64) * cell[1],
+        ]
+    }
+}
+
+fn cell_size(rect: Rect<f32>, dim: [usize; 2]) -> [f32; 2] {
+    [
+        (rect.x.end - rect.x.start) / dim[0] as f32,
+        (rect.y.end - rect.y.start) / dim[1] as f32,
+    ]
+}
+
+///Points distributed according to the density grid.
+pub fn density_iter<T: Coord>(
+    grid: &DensityGrid,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    density_iter_from_rng(grid, rand::thread_rng())
+}
+
+///Same as [`density_iter`] but reproducible from a seed.
+pub fn density_iter_seeded<T: Coord>(
+    grid: &DensityGrid,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    density_iter_from_rng(grid, seeded_rng(seed))
+}
+
+///Same as [`density_iter`] but draws from the provided rng.
+pub fn density_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    grid: &DensityGrid,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    let rect = grid.rect().cast_coord();
+    let grid = Arc::new(grid.clone());
+    DistIter::new(core::iter::repeat_with(move || {
+        point_from_f64(grid.sample(&mut rng))
+    }))
+    .with_rect(rect)
+}
+
+///Exactly `num` points distributed according to the density grid.
+///Instead of drawing each point independently, the cumulative density is split
+///into `num` equal strata and one point is drawn from each, so every cell
+///receives close to its expected share of points.
+pub fn density_stratified_iter<T: Coord>(
+    grid: &DensityGrid,
+    num: usize,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + DoubleEndedIterator + FusedIterator + Clone
+{
+    density_stratified_iter_from_rng(grid, num, rand::thread_rng())
+}
+
+///Same as [`density_stratified_iter`] but reproducible from a seed.
+pub fn density_stratified_iter_seeded<T: Coord>(
+    grid: &DensityGrid,
+    num: usize,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + FusedIterator
+       + Clone
+       + Send
+       + Sync {
+    density_stratified_iter_from_rng(grid, num, seeded_rng(seed))
+}
+
+///Same as [`density_stratified_iter`] but draws from the provided rng.
+pub fn density_stratified_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    grid: &DensityGrid,
+    num: usize,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + DoubleEndedIterator + FusedIterator + Clone
+{
+    let rect = grid.rect().cast_coord();
+    let grid = Arc::new(grid.clone());
+    DistIter::new((0..num).map(move |k| {
+        let u = (k as f64 + unit_f32(&mut rng) as f64) / num as f64;
+        let i = grid.invert_cdf(u);
+        point_from_f64(grid.point_in_cell(i, &mut rng))
+    }))
+    .with_len(num)
+    .with_rect(rect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///The probability of picking each index implied by the table.
+    fn implied(table: &AliasTable) -> Vec<f64> {
+        let n = table.prob.len();
+        let mut p: Vec<f64> = table.prob.iter().map(|&q| q / n as f64).collect();
+        for (i, &a) in table.alias.iter().enumerate() {
+            p[a] += (1.0 - table.prob[i]) / n as f64;
+        }
+        p
+    }
+
+    #[test]
+    fn alias_table_matches_weights() {
+        for weights in [
+            vec![1.0, 3.0],
+            vec![0.1, 0.2, 0.7],
+            vec![0.0, 5.0, 0.0, 1.0, 1e-9, 7.0, 3.0],
+            vec![1.0; 9],
+        ] {
+            let table = AliasTable::new(&weights);
+            let total: f64 = weights.iter().sum();
+            for (p, w) in implied(&table).iter().zip(weights.iter()) {
+                assert!((p - w / total).abs() < 1e-12, "{:?}", weights);
+            }
+            assert!(table.prob.iter().all(|&p| (0.0..=1.0).contains(&p)));
+        }
+    }
+
+    fn left_fraction(points: &[[f64; 2]]) -> f64 {
+        points.iter().filter(|p| p[0] < 1.0).count() as f64 / points.len() as f64
+    }
+
+    #[test]
+    fn frequencies_follow_weights() {
+        let grid = DensityGrid::new(rect(0.0, 2.0, 0.0, 1.0), [2, 1], &[1.0, 3.0]).unwrap();
+
+        let points: Vec<[f64; 2]> = density_iter_seeded(&grid, 1).take(20000).collect();
+        assert!((left_fraction(&points) - 0.25).abs() < 0.02);
+
+        let points: Vec<[f64; 2]> = density_stratified_iter_seeded(&grid, 1000, 1).collect();
+        assert_eq!(left_fraction(&points), 0.25);
+    }
+
+    #[test]
+    fn zero_density_cells_are_empty() {
+        //Only the cells on the diagonal have any density.
+        let values = [1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.5];
+        let grid = DensityGrid::new(rect(0.0, 3.0, 0.0, 3.0), [3, 3], &values).unwrap();
+
+        let random: Vec<[f64; 2]> = density_iter_seeded(&grid, 2).take(5000).collect();
+        let stratified: Vec<[f64; 2]> = density_stratified_iter_seeded(&grid, 5000, 2).collect();
+        for p in random.iter().chain(stratified.iter()) {
+            assert_eq!(p[0].floor(), p[1].floor(), "{:?}", p);
+        }
+
+        for u in [0.0, 1.0 / 3.5, 0.5, 0.999_999] {
+            assert!([0, 4, 8].contains(&grid.invert_cdf(u)));
+        }
+        assert_eq!(grid.invert_cdf(0.0), 0);
+        assert_eq!(grid.invert_cdf(0.999_999), 8);
+    }
+
+    #[test]
+    fn errors() {
+        let r = rect(0.0, 1.0, 0.0, 1.0);
+        assert_eq!(
+            DensityGrid::new(r, [0, 3], &[]).err(),
+            Some(DensityError::NoCells)
+        );
+        assert_eq!(
+            DensityGrid::new(r, [2, 2], &[1.0; 3]).err(),
+            Some(DensityError::WrongLength)
+        );
+        for bad in [-1.0, f32::NAN, f32::INFINITY] {
+            assert_eq!(
+                DensityGrid::new(r, [2, 1], &[1.0, bad]).err(),
+                Some(DensityError::InvalidDensity)
+            );
+        }
+        assert_eq!(
+            DensityGrid::new(r, [2, 1], &[0.0, 0.0]).err(),
+            Some(DensityError::ZeroTotal)
+        );
+        assert_eq!(
+            DensityGrid::from_fn(r, [4, 4], |_| 0.0).err(),
+            Some(DensityError::ZeroTotal)
+        );
+    }
+}
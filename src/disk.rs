@@ -0,0 +1,150 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use rand::RngCore;
+
+use crate::coord::*;
+use crate::seeded_rng;
+use crate::unit_f32;
+use crate::Dist;
+use crate::DistIter;
+
+///A disk, annulus, ellipse or circular sector to sample points in.
+///
+///Every region is a sector of an annulus that is stretched into an ellipse and then rotated,
+///so the constructors can be combined, for example an elliptical annulus.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DiskRegion {
+    center: [f64; 2],
+    radius: [f64; 2],
+    inner: f64,
+    angles: [f64; 2],
+    rotation: f64,
+}
+
+impl DiskRegion {
+    pub fn disk(center: [f64; 2], radius: f64) -> DiskRegion {
+        assert!(radius >= 0.0, "radius must not be negative");
+        DiskRegion {
+            center,
+            radius: [radius, radius],
+            inner: 0.0,
+            angles: [0.0, core::f64::consts::TAU],
+            rotation: 0.0,
+        }
+    }
+
+    pub fn annulus(center: [f64; 2], inner_radius: f64, outer_radius: f64) -> DiskRegion {
+        DiskRegion::disk(center, outer_radius).with_inner_ratio(if outer_radius > 0.0 {
+            inner_radius / outer_radius
+        } else {
+            0.0
+        })
+    }
+
+    ///An ellipse with the given radius along each of its own axes,
+    ///rotated counter clockwise by `rotation` radians.
+    pub fn ellipse(center: [f64; 2], radius: [f64; 2], rotation: f64) -> DiskRegion {
+        assert!(
+            radius[0] >= 0.0 && radius[1] >= 0.0,
+            "radius must not be negative"
+        );
+        DiskRegion {
+            radius,
+            rotation,
+            ..DiskRegion::disk(center, 0.0)
+        }
+    }
+
+    ///The part of a disk between the two angles in radians, measured counter clockwise.
+    pub fn sector(center: [f64; 2], radius: f64, start_angle: f64, end_angle: f64) -> DiskRegion {
+        DiskRegion::disk(center, radius).with_angles(start_angle, end_angle)
+    }
+
+    ///Cut out the middle of the region, leaving only the part
+    ///further than `ratio` of the way out from the center.
+    pub fn with_inner_ratio(mut self, ratio: f64) -> DiskRegion {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "ratio must be between zero and one"
+        );
+        self.inner = ratio;
+        self
+    }
+
+    ///Only keep the part of the region between the two angles in radians.
+    pub fn with_angles(mut self, start_angle: f64, end_angle: f64) -> DiskRegion {
+        assert!(
+            start_angle <= end_angle,
+            "start angle must not be after end angle"
+        );
+        self.angles = [start_angle, end_angle];
+        self
+    }
+
+    ///Map a point in the unit square to a point in the region,
+    ///such that uniform points in the square become uniform points in the region.
+    pub fn from_unit(&self, [u, v]: [f64; 2]) -> [f64; 2] {
+        let inner2 = self.inner * self.inner;
+        let r = (inner2 + u * (1.0 - inner2)).sqrt();
+        let angle = self.angles[0] + v * (self.angles[1] - self.angles[0]);
+
+        let (s, c) = angle.sin_cos();
+        let local = [self.radius[0] * r * c, self.radius[1] * r * s];
+
+        let (rs, rc) = self.rotation.sin_cos();
+        [
+            self.center[0] + rc * local[0] - rs * local[1],
+            self.center[1] + rs * local[0] + rc * local[1],
+        ]
+    }
+
+    ///A rect that contains the whole ellipse the region is a part of.
+    pub fn bounding_rect(&self) -> Rect<f64> {
+        let (s, c) = self.rotation.sin_cos();
+        let [rx, ry] = self.radius;
+        let ex = ((rx * c).powi(2) + (ry * s).powi(2)).sqrt();
+        let ey = ((rx * s).powi(2) + (ry * c).powi(2)).sqrt();
+        Rect::new(
+            self.center[0] - ex,
+            self.center[0] + ex,
+            self.center[1] - ey,
+            self.center[1] + ey,
+        )
+    }
+}
+
+///Uniformly random points inside the region.
+pub fn disk_iter<T: Coord>(
+    region: DiskRegion,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    disk_iter_from_rng(region, rand::thread_rng())
+}
+
+///Same as [`disk_iter`] but reproducible from a seed.
+pub fn disk_iter_seeded<T: Coord>(
+    region: DiskRegion,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    disk_iter_from_rng(region, seeded_rng(seed))
+}
+
+///Same as [`disk_iter`] but draws from the provided rng.
+pub fn disk_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    region: DiskRegion,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    DistIter::new(core::iter::repeat_with(move || {
+        let u = unit_f32(&mut rng) as f64;
+        let v = unit_f32(&mut rng) as f64;
+        point_from_f64(region.from_unit([u, v]))
+    }))
+    .with_rect(region.bounding_rect().cast_coord())
+}
+
+///Evenly spread out points inside the region, using the R2 low discrepancy sequence.
+pub fn disk_quasi_iter<T: Coord>(
+    region: DiskRegion,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    DistIter::new((0..).map(move |i| point_from_f64(region.from_unit(crate::quasi::r2(i)))))
+        .with_rect(region.bounding_rect().cast_coord())
+}
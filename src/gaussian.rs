@@ -0,0 +1,276 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use rand::RngCore;
+
+use crate::coord::*;
+use crate::seeded_rng;
+use crate::unit_f32;
+use crate::Dist;
+use crate::DistIter;
+
+///How many times a point outside of the clip rect is redrawn before it is clamped into it.
+const MAX_CLIP_ATTEMPTS: usize = 64;
+
+///A 2d normal distribution with a mean and a full covariance matrix.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Gaussian {
+    mean: [f64; 2],
+    //Lower triangular cholesky factor of the covariance matrix.
+    chol: [f64; 3],
+}
+
+impl Gaussian {
+    ///Create a gaussian from its mean and covariance matrix.
+    ///Panics if the covariance matrix is not symmetric positive semi-definite.
+    pub fn new(mean: [f64; 2], cov: [[f64; 2]; 2]) -> Gaussian {
+        let [[a, b], [b2, c]] = cov;
+        assert!(b == b2, "covariance matrix must be symmetric");
+        assert!(a >= 0.0 && c >= 0.0, "variances must not be negative");
+
+        let l00 = a.sqrt();
+        let l10 = if l00 > 0.0 { b / l00 } else { 0.0 };
+        let rem = c - l10 * l10;
+        assert!(
+            rem >= -1e-9 * c.max(1.0),
+            "covariance matrix must be positive semi-definite"
+        );
+        let l11 = rem.max(0.0).sqrt();
+
+        Gaussian {
+            mean,
+            chol: [l00, l10, l11],
+        }
+    }
+
+    ///A gaussian with the same standard deviation along both axes.
+    pub fn isotropic(mean: [f64; 2], std_dev: f64) -> Gaussian {
+        Gaussian::rotated(mean, [std_dev, std_dev], 0.0)
+    }
+
+    ///A gaussian with a standard deviation along each of its own axes,
+    ///rotated counter clockwise by `angle` radians.
+    pub fn rotated(mean: [f64; 2], std_dev: [f64; 2], angle: f64) -> Gaussian {
+        let (s, c) = angle.sin_cos();
+        let [vx, vy] = [std_dev[0] * std_dev[0], std_dev[1] * std_dev[1]];
+
+        //R*diag(vx,vy)*R^T
+        let xx = c * c * vx + s * s * vy;
+        let xy = c * s * (vx - vy);
+        let yy = s * s * vx + c * c * vy;
+        Gaussian::new(mean, [[xx, xy], [xy, yy]])
+    }
+
+    pub fn mean(&self) -> [f64; 2] {
+        self.mean
+    }
+
+    ///Draw one point.
+    pub fn sample<R: RngCore>(&self, rng: &mut R) -> [f64; 2] {
+        let [z0, z1] = standard_normal(rng);
+        let [l00, l10, l11] = self.chol;
+        [self.mean[0] + l00 * z0, self.mean[1] + l10 * z0 + l11 * z1]
+    }
+}
+
+///Two independent standard normal values using the box-muller transform.
+pub(crate) fn standard_normal<R: RngCore>(rng: &mut R) -> [f64; 2] {
+    //Use (0,1] so that the log is finite.
+    let u1 = 1.0 - unit_f32(rng) as f64;
+    let u2 = unit_f32(rng) as f64;
+    let r = (-2.0 * u1.ln()).sqrt();
+    let (s, c) = (core::f64::consts::TAU * u2).sin_cos();
+    [r * c, r * s]
+}
+
+///Points drawn from a gaussian.
+pub fn gaussian_iter<T: Coord>(
+    gaussian: Gaussian,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    gaussian_iter_from_rng(gaussian, rand::thread_rng())
+}
+
+///Same as [`gaussian_iter`] but reproducible from a seed.
+pub fn gaussian_iter_seeded<T: Coord>(
+    gaussian: Gaussian,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    gaussian_iter_from_rng(gaussian, seeded_rng(seed))
+}
+
+///Same as [`gaussian_iter`] but draws from the provided rng.
+pub fn gaussian_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    gaussian: Gaussian,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    DistIter::new(core::iter::repeat_with(move || {
+        point_from_f64(gaussian.sample(&mut rng))
+    }))
+}
+
+///Points drawn from several weighted gaussian clusters.
+///
+///If a clip rect is given, points outside of it are redrawn.
+///A point that still lands outside after many attempts is clamped into the rect,
+///so clusters far outside of the rect will pile up along its border.
+///
+///Panics if there are no clusters, if a weight is negative,
+///or if the clip rect is inverted or has NaN coordinates.
+pub fn mixture_iter<T: Coord>(
+    clusters: &[(f64, Gaussian)],
+    clip: Option<Rect<T>>,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    mixture_iter_from_rng(clusters, clip, rand::thread_rng())
+}
+
+///Same as [`mixture_iter`] but reproducible from a seed.
+pub fn mixture_iter_seeded<T: Coord>(
+    clusters: &[(f64, Gaussian)],
+    clip: Option<Rect<T>>,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    mixture_iter_from_rng(clusters, clip, seeded_rng(seed))
+}
+
+///Same as [`mixture_iter`] but draws from the provided rng.
+pub fn mixture_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    clusters: &[(f64, Gaussian)],
+    clip: Option<Rect<T>>,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    assert!(!clusters.is_empty(), "at least one cluster is required");
+    assert!(
+        clusters.iter().all(|(w, _)| *w >= 0.0),
+        "weights must not be negative"
+    );
+
+    let mut total = 0.0;
+    let cumulative: Vec<_> = clusters
+        .iter()
+        .map(|(w, g)| {
+            total += w;
+            (total, *g)
+        })
+        .collect();
+    assert!(total > 0.0, "the sum of the weights must be positive");
+
+    let clip_f64 = clip.map(rect_to_f64);
+    if let Some(clip) = clip_f64 {
+        assert!(
+            clip.x.start <= clip.x.end && clip.y.start <= clip.y.end,
+            "clip rect must not be inverted"
+        );
+    }
+
+    let mut sample = move || {
+        let t = unit_f32(&mut rng) as f64 * total;
+        let i = cumulative
+            .partition_point(|(c, _)| *c <= t)
+            .min(cumulative.len() - 1);
+        cumulative[i].1.sample(&mut rng)
+    };
+
+    let it = DistIter::new(core::iter::repeat_with(move || {
+        let clip = match clip_f64 {
+            Some(clip) => clip,
+            None => return point_from_f64(sample()),
+        };
+
+        let mut p = sample();
+        for _ in 1..MAX_CLIP_ATTEMPTS {
+            if clip.contains_point(vec2(p[0], p[1])) {
+                break;
+            }
+            p = sample();
+        }
+        let x = p[0].clamp(clip.x.start, clip.x.end);
+        let y = p[1].clamp(clip.y.start, clip.y.end);
+        point_from_f64([x, y])
+    }));
+
+    match clip {
+        Some(clip) => it.with_rect(clip),
+        None => it,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_covariance_matches() {
+        let mean = [3.0, -2.0];
+        let angle = 0.6f64;
+        let g = Gaussian::rotated(mean, [2.0, 0.5], angle);
+
+        let n = 200_000;
+        let points: Vec<[f64; 2]> = gaussian_iter_seeded(g, 3).take(n).collect();
+        let m = [
+            points.iter().map(|p| p[0]).sum::<f64>() / n as f64,
+            points.iter().map(|p| p[1]).sum::<f64>() / n as f64,
+        ];
+        assert!((m[0] - mean[0]).abs() < 0.02);
+        assert!((m[1] - mean[1]).abs() < 0.02);
+
+        //Project onto the axes of the gaussian, where the covariance is diagonal.
+        let (s, c) = angle.sin_cos();
+        let axes = [[c, s], [-s, c]];
+        let cov = |u: [f64; 2], v: [f64; 2]| {
+            points
+                .iter()
+                .map(|p| {
+                    let d = [p[0] - m[0], p[1] - m[1]];
+                    (d[0] * u[0] + d[1] * u[1]) * (d[0] * v[0] + d[1] * v[1])
+                })
+                .sum::<f64>()
+                / n as f64
+        };
+        assert!((cov(axes[0], axes[0]) - 4.0).abs() < 0.03 * 4.0);
+        assert!((cov(axes[1], axes[1]) - 0.25).abs() < 0.03 * 0.25);
+        assert!(cov(axes[0], axes[1]).abs() < 0.03);
+    }
+
+    #[test]
+    fn degenerate_covariance() {
+        //Positive semi-definite but singular, so every point is on the line y=2x.
+        let g = Gaussian::new([0.0, 0.0], [[1.0, 2.0], [2.0, 4.0]]);
+        let mut rng = seeded_rng(1);
+        for _ in 0..100 {
+            let [x, y] = g.sample(&mut rng);
+            assert!((y - 2.0 * x).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "positive semi-definite")]
+    fn not_positive_semi_definite() {
+        Gaussian::new([0.0, 0.0], [[1.0, 2.0], [2.0, 1.0]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "symmetric")]
+    fn not_symmetric() {
+        Gaussian::new([0.0, 0.0], [[1.0, 0.5], [0.0, 1.0]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "clip rect must not be inverted")]
+    fn inverted_clip() {
+        let clusters = [(1.0, Gaussian::isotropic([0.0, 0.0], 1.0))];
+        let _ = mixture_iter_seeded(&clusters, Some(Rect::new(1.0f32, -1.0, -1.0, 1.0)), 0);
+    }
+
+    #[test]
+    fn clip_keeps_points_inside() {
+        let clusters = [
+            (1.0, Gaussian::isotropic([0.0, 0.0], 1.0)),
+            (3.0, Gaussian::isotropic([20.0, 0.0], 1.0)),
+        ];
+        let clip = Rect::new(-2.0f64, 2.0, -2.0, 2.0);
+        let it = mixture_iter_seeded(&clusters, Some(clip), 4);
+        assert_eq!(it.bounding_rect(), Some(clip));
+        assert!(it
+            .take(1000)
+            .all(|[x, y]| (-2.0..=2.0).contains(&x) && (-2.0..=2.0).contains(&y)));
+    }
+}
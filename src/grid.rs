@@ -1,5 +1,7 @@
 use axgeom::*;
 
+use crate::Dist;
+
 ///Create a grid from a center point.
 #[deprecated(since = "0.3.1", note = "use grid_iter() instead")]
 pub fn from_center(
@@ -62,53 +64,109 @@ pub fn from_center(
     }
 }
 
+///Why a grid could not be created.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GridError {
+    ///Zero points were requested.
+    ZeroCount,
+    ///The rect has no area or is not finite.
+    DegenerateRect,
+}
+
+impl core::fmt::Display for GridError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            GridError::ZeroCount => write!(f, "zero points were requested"),
+            GridError::DegenerateRect => write!(f, "the rect has no area or is not finite"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+///Find how many columns and rows are needed to fit exactly `num_bots`
+///points in the rect, and the spacing between them.
+///The last row may be partially filled.
+pub(crate) fn grid_layout(
+    num_bots: usize,
+    rect: Rect<f64>,
+) -> Result<([usize; 2], Vec2<f64>), GridError> {
+    let width = rect.x.end - rect.x.start;
+    let height = rect.y.end - rect.y.start;
+
+    if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+        return Err(GridError::DegenerateRect);
+    }
+    if num_bots == 0 {
+        return Err(GridError::ZeroCount);
+    }
+
+    let aspect_ratio = width / height;
+
+    //w*h=num_bots
+    //w/h=width/height
+    //solve for w
+
+    //h=num_bots/w
+    //w^2/num_bots=width/height
+    //w=sqrt(num_bots*(width/height))
+
+    let w = ((num_bots as f64 * aspect_ratio).sqrt().round() as usize).clamp(1, num_bots);
+    let h = num_bots.div_ceil(w);
+
+    let spacing = vec2(width / w as f64, height / h as f64);
+    Ok(([w, h], spacing))
+}
+
 ///Create a grid where instead of specifying the spacing,
 ///the user specifies the rectangle to fill.
+///Yields exactly the requested number of points.
 #[derive(Clone)]
 pub struct Grid {
     rect: Rect<f32>,
     grid_dim: Vec2<usize>,
     cursor: Vec2<usize>,
     spacing: Vec2<f32>,
+    remaining: usize,
 }
 
 impl Grid {
+    ///Returns an empty grid if the rect is degenerate.
     pub fn new(rect: Rect<f32>, num_bots: usize) -> Grid {
-        let width = rect.x.end - rect.x.start;
-        let height = rect.y.end - rect.y.start;
-
-        let aspect_ratio = width / height;
-
-        //w*h=num_bots
-        //w/h=width/height
-        //solve for sx and sy
-
-        //w=num_bots/h
-        //num_bots/h^2=width/height
-        //h^2=num_bots/(width/height);
-        //h=sqrt(num_bots/(width/height));
-
-        let h = (num_bots as f32 / aspect_ratio).sqrt().ceil() as usize;
-        let w = num_bots / h;
+        Grid::try_new(rect, num_bots).unwrap_or(Grid {
+            rect,
+            grid_dim: vec2(0, 0),
+            cursor: vec2(0, 0),
+            spacing: vec2(0.0, 0.0),
+            remaining: 0,
+        })
+    }
 
-        let grid_dim = vec2(w, h);
-        let cursor = vec2(0, 0);
-        let spacing = vec2(width / w as f32, height / h as f32);
+    pub fn try_new(rect: Rect<f32>, num_bots: usize) -> Result<Grid, GridError> {
+        let (grid_dim, spacing) = grid_layout(num_bots, rect.inner_as())?;
+        let spacing = spacing.inner_as();
 
-        Grid {
+        Ok(Grid {
             rect,
-            grid_dim,
-            cursor,
+            grid_dim: vec2(grid_dim[0], grid_dim[1]),
+            cursor: vec2(0, 0),
             spacing,
-        }
+            remaining: num_bots,
+        })
     }
 }
 
 impl std::iter::FusedIterator for Grid {}
 
+impl ExactSizeIterator for Grid {}
+
 impl Iterator for Grid {
     type Item = Vec2<f32>;
     fn next(&mut self) -> Option<Vec2<f32>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
         let topstart = vec2(self.rect.x.start, self.rect.y.start);
 
         let kk = vec2(
@@ -118,21 +176,110 @@ impl Iterator for Grid {
         let ans = topstart + kk;
 
         //increment
-        if self.cursor.x < self.grid_dim.x {
-            self.cursor.x += 1;
-        } else {
-            if self.cursor.y < self.grid_dim.y {
-                self.cursor.x = 0;
-                self.cursor.y += 1;
-            } else {
-                return None;
-            }
+        self.cursor.x += 1;
+        if self.cursor.x >= self.grid_dim.x {
+            self.cursor.x = 0;
+            self.cursor.y += 1;
         }
+        self.remaining -= 1;
 
         debug_assert!(!ans.x.is_nan());
         debug_assert!(!ans.y.is_nan());
         Some(ans)
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl Dist for Grid {
+    type Num = f32;
+    fn exact_len(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+    fn bounding_rect(&self) -> Option<Rect<f32>> {
+        Some(self.rect)
+    }
 }
 
-//impl Dist<f32> for Grid{}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_rect_iter;
+    use crate::try_grid_rect_iter;
+
+    fn rects() -> Vec<Rect<f32>> {
+        vec![
+            rect(0.0, 100.0, 0.0, 100.0),
+            rect(-50.0, 50.0, 10.0, 20.0),
+            //Aspect ratios far larger than the counts below.
+            rect(0.0, 10000.0, 0.0, 1.0),
+            rect(0.0, 1.0, 0.0, 10000.0),
+            rect(0.0, 1e-3, 0.0, 1e3),
+        ]
+    }
+
+    fn inside(r: Rect<f32>, [x, y]: [f32; 2]) -> bool {
+        r.x.start <= x && x < r.x.end && r.y.start <= y && y < r.y.end
+    }
+
+    #[test]
+    fn exact_count_inside_rect() {
+        for r in rects() {
+            for n in [1, 2, 3, 5, 7, 10, 99, 1000] {
+                let it = grid_rect_iter(n, r);
+                assert_eq!(it.len(), n);
+                assert_eq!(it.clone().count(), n);
+                assert_eq!(it.clone().rev().count(), n);
+                assert!(it.clone().all(|p| inside(r, p)), "{:?} {}", r, n);
+
+                let it = try_grid_rect_iter(n, r).unwrap();
+                assert_eq!(it.len(), n);
+
+                let grid = Grid::new(r, n);
+                assert_eq!(grid.len(), n);
+                assert_eq!(grid.exact_len(), Some(n));
+                let points: Vec<_> = grid.collect();
+                assert_eq!(points.len(), n);
+                assert!(points.iter().all(|p| inside(r, [p.x, p.y])));
+            }
+        }
+    }
+
+    #[test]
+    fn layout_fits_count() {
+        for r in rects() {
+            for n in [1, 2, 3, 10, 1000] {
+                let ([w, h], spacing) = grid_layout(n, r.inner_as()).unwrap();
+                assert!(w >= 1 && h >= 1);
+                assert!(w * h >= n && w * (h - 1) < n);
+                assert!(spacing.x > 0.0 && spacing.y > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let r = rect(0.0, 1.0, 0.0, 1.0);
+        assert_eq!(grid_layout(0, r).err(), Some(GridError::ZeroCount));
+        assert_eq!(
+            try_grid_rect_iter(0, rect(0.0f32, 1.0, 0.0, 1.0)).err(),
+            Some(GridError::ZeroCount)
+        );
+        for bad in [
+            rect(0.0, 0.0, 0.0, 1.0),
+            rect(0.0, 1.0, 1.0, 1.0),
+            rect(1.0, 0.0, 0.0, 1.0),
+            rect(0.0, f32::INFINITY, 0.0, 1.0),
+            rect(0.0, f32::NAN, 0.0, 1.0),
+        ] {
+            assert_eq!(
+                try_grid_rect_iter(5, bad).err(),
+                Some(GridError::DegenerateRect)
+            );
+            assert_eq!(Grid::try_new(bad, 5).err(), Some(GridError::DegenerateRect));
+            assert_eq!(grid_rect_iter(5, bad).len(), 0);
+            assert_eq!(Grid::new(bad, 5).count(), 0);
+        }
+    }
+}
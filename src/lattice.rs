@@ -0,0 +1,159 @@
+use axgeom::*;
+
+use crate::coord::*;
+use crate::Dist;
+use crate::DistIter;
+
+///Lays out rows of points where every odd row is shifted by half the horizontal spacing.
+///Points may lie past the end of the rect.
+fn staggered(
+    rect: Rect<f64>,
+    spacing: Vec2<f64>,
+    dim: [usize; 2],
+) -> impl ExactSizeIterator<Item = [f64; 2]> + DoubleEndedIterator + Clone + Send + Sync {
+    let topstart = vec2(rect.x.start, rect.y.start);
+    let [w, h] = dim;
+    (0..w * h).map(move |i| {
+        let [x, y] = [i % w, i / w];
+        let offset = if y % 2 == 1 { 0.5 } else { 0.0 };
+        let v = topstart + vec2(x as f64 + offset, y as f64).scale(spacing);
+        [v.x, v.y]
+    })
+}
+
+///Fill the rect with a staggered lattice with the given spacing.
+fn staggered_spacing<T: Coord>(
+    bounds: Rect<T>,
+    spacing: Vec2<f64>,
+) -> impl Dist<Item = [T; 2], Num = T> + Clone + Send + Sync {
+    let rect = rect_to_f64(bounds);
+    assert!(
+        spacing.x > 0.0 && spacing.y > 0.0,
+        "spacing must be positive"
+    );
+    let width = (rect.x.end - rect.x.start).max(0.0);
+    let height = (rect.y.end - rect.y.start).max(0.0);
+    let dim = [
+        (width / spacing.x).ceil() as usize,
+        (height / spacing.y).ceil() as usize,
+    ];
+    DistIter::new(
+        staggered(rect, spacing, dim)
+            .filter(move |&[x, y]| x < rect.x.end && y < rect.y.end)
+            .map(point_from_f64),
+    )
+    .with_rect(bounds)
+}
+
+///Fill the rect with exactly `num_bots` points of a staggered lattice,
+///where the vertical spacing is `row_ratio` times the horizontal spacing.
+///Yields nothing if the rect has no area.
+fn staggered_count<T: Coord>(
+    num_bots: usize,
+    bounds: Rect<T>,
+    row_ratio: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + DoubleEndedIterator + Clone + Send + Sync
+{
+    let rect = rect_to_f64(bounds);
+    let width = rect.x.end - rect.x.start;
+    let height = rect.y.end - rect.y.start;
+
+    let (spacing, dim) = if num_bots == 0 || !(width > 0.0 && height > 0.0) {
+        (vec2(0.0, 0.0), [0, 0])
+    } else {
+        //each point takes up an area of sx*sy=sx*sx*row_ratio
+        //sx*sx*row_ratio*num_bots=width*height
+        let sx = (width * height / (row_ratio * num_bots as f64)).sqrt();
+
+        let w = ((width / sx).ceil() as usize).clamp(1, num_bots);
+        let h = num_bots.div_ceil(w);
+
+        //the odd rows are shifted by half a spacing, so leave room for that.
+        //This keeps every point inside the rect.
+        let sx = if h > 1 {
+            width / (w as f64 + 0.5)
+        } else {
+            width / w as f64
+        };
+        (vec2(sx, height / h as f64), [w, h])
+    };
+
+    let num_bots = num_bots.min(dim[0] * dim[1]);
+    DistIter::new(
+        staggered(rect, spacing, dim)
+            .take(num_bots)
+            .map(point_from_f64),
+    )
+    .with_len(num_bots)
+    .with_rect(bounds)
+}
+
+///Fill the rect with a hexagonal lattice where every point is `spacing`
+///away from its six neighbours.
+pub fn hex_iter<T: Coord>(
+    rect: Rect<T>,
+    spacing: T,
+) -> impl Dist<Item = [T; 2], Num = T> + Clone + Send + Sync {
+    let spacing = spacing.to_f64();
+    staggered_spacing(rect, vec2(spacing, spacing * 3.0f64.sqrt() / 2.0))
+}
+
+///Fill the rect with exactly `num_bots` points laid out in an approximately hexagonal lattice.
+pub fn hex_rect_iter<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + DoubleEndedIterator + Clone + Send + Sync
+{
+    staggered_count(num_bots, rect, 3.0f64.sqrt() / 2.0)
+}
+
+///Fill the rect with a checkerboard lattice.
+///Squares of side length `spacing` centered at each point only touch at the corners.
+///```text
+///# # # #
+/// # # #
+///# # # #
+///```
+pub fn checker_iter<T: Coord>(
+    rect: Rect<T>,
+    spacing: T,
+) -> impl Dist<Item = [T; 2], Num = T> + Clone + Send + Sync {
+    let spacing = spacing.to_f64();
+    staggered_spacing(rect, vec2(spacing * 2.0, spacing))
+}
+
+///Fill the rect with exactly `num_bots` points laid out in a checkerboard lattice.
+///The lattice is stretched to fill the rect, so the squares may not exactly touch at the corners.
+pub fn checker_rect_iter<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + DoubleEndedIterator + Clone + Send + Sync
+{
+    staggered_count(num_bots, rect, 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_iters_are_exact_and_inside() {
+        for &r in &[rect(0.0, 100.0, 0.0, 100.0), rect(-5.0, 1000.0, 3.0, 4.0)] {
+            for n in [1, 2, 3, 10, 99, 1000] {
+                for it in [
+                    hex_rect_iter::<f64>(n, r).collect::<Vec<_>>(),
+                    checker_rect_iter::<f64>(n, r).collect::<Vec<_>>(),
+                ] {
+                    assert_eq!(it.len(), n);
+                    assert!(it.iter().all(|&[x, y]| r.x.start <= x
+                        && x < r.x.end
+                        && r.y.start <= y
+                        && y < r.y.end));
+                }
+                assert_eq!(hex_rect_iter::<f64>(n, r).len(), n);
+                assert_eq!(checker_rect_iter::<f64>(n, r).rev().count(), n);
+            }
+        }
+        assert_eq!(hex_rect_iter::<f64>(10, rect(0.0, 0.0, 0.0, 1.0)).len(), 0);
+    }
+}
@@ -10,67 +10,169 @@ use rand::prelude::*;
 ///Produces grid distributions
 pub mod grid;
 
-///Produces an archimedean spiral distribution
+///Produces archimedean, logarithmic, fermat and multi-arm spiral distributions
 pub mod spiral;
 
 ///Produces a random distribution over a rectangular area
 pub mod uniform_rand;
 
-pub fn grid_rect_iter(
-    num_bots: usize,
-    rect: Rect<f32>,
-) -> impl Iterator<Item = [f32; 2]> + Clone + Send + Sync {
-    let width = rect.x.end - rect.x.start;
-    let height = rect.y.end - rect.y.start;
+///Produces staggered lattice distributions, such as hexagonal or checkerboard
+pub mod lattice;
+
+///Produces nested rectangles that all overlap each other
+pub mod russian_doll;
+
+///Produces grids of AABBs that are separated by a configurable gap
+pub mod one_apart;
+
+///Adapters that wrap points in AABBs
+pub mod aabb;
+
+///Produces bots with an id, position, velocity and radius
+pub mod world;
+
+///Brute force helpers to validate the results of spatial data structures
+pub mod testing;
+
+///Conversions between the coordinate types of distributions
+pub mod coord;
+
+///Produces low discrepancy (quasi random) sequences over a rectangular area
+pub mod quasi;
+
+///Produces gaussian and mixture of gaussian distributions
+pub mod gaussian;
+
+///Produces thomas and matérn clustered point processes
+pub mod cluster;
 
-    let aspect_ratio = width / height;
+///Produces uniform distributions inside of disks, annuli, ellipses and sectors
+pub mod disk;
 
-    //w*h=num_bots
-    //w/h=width/height
-    //solve for sx and sy
+///Produces uniform distributions inside of polygons, which may have holes
+pub mod polygon;
 
-    //w=num_bots/h
-    //num_bots/h^2=width/height
-    //h^2=num_bots/(width/height);
-    //h=sqrt(num_bots/(width/height));
+///Produces distributions that follow a user provided density
+pub mod density;
 
-    let h = (num_bots as f32 / aspect_ratio).sqrt().ceil() as usize;
-    let w = num_bots / h;
+///Produces distributions from grayscale images and bitmap masks
+pub mod bitmap;
 
-    let grid_dim = [w, h];
-    let spacing = vec2(width / w as f32, height / h as f32);
+///Adapters that translate, rotate, scale, shear, mirror or fit any distribution into a rect
+pub mod transform;
+
+mod poisson;
+
+use coord::CastCoord;
+use coord::Coord;
+
+///Fill the rect with a grid of exactly `num_bots` points.
+///The last row may be partially filled.
+///Yields nothing if the rect has no area. See [`try_grid_rect_iter`].
+pub fn grid_rect_iter<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + DoubleEndedIterator + Clone + Send + Sync
+{
+    match grid::grid_layout(num_bots, coord::rect_to_f64(rect)) {
+        Ok((grid_dim, spacing)) => grid_rect(num_bots, grid_dim, spacing, rect),
+        Err(_) => grid_rect(0, [0, 0], vec2(0.0, 0.0), rect),
+    }
+}
+
+///Same as [`grid_rect_iter`] but returns an error
+///if zero points are requested or the rect has no area.
+pub fn try_grid_rect_iter<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+) -> Result<
+    impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + DoubleEndedIterator + Clone + Send + Sync,
+    grid::GridError,
+> {
+    let (grid_dim, spacing) = grid::grid_layout(num_bots, coord::rect_to_f64(rect))?;
+    Ok(grid_rect(num_bots, grid_dim, spacing, rect))
+}
 
-    let topstart = vec2(rect.x.start, rect.y.start);
-    grid_iter(grid_dim).map(move |[x, y]| {
+fn grid_rect<T: Coord>(
+    num_bots: usize,
+    grid_dim: [usize; 2],
+    spacing: Vec2<f64>,
+    rect: Rect<T>,
+) -> DistIter<T, impl ExactSizeIterator<Item = [T; 2]> + DoubleEndedIterator + Clone> {
+    let topstart = vec2(rect.x.start.to_f64(), rect.y.start.to_f64());
+    let it = grid_iter(grid_dim).take(num_bots).map(move |[x, y]| {
         let v = topstart + vec2(x, y).inner_as().scale(spacing);
-        [v.x, v.y]
-    })
+        coord::point_from_f64([v.x, v.y])
+    });
+    DistIter::new(it).with_len(num_bots).with_rect(rect)
 }
 
-//TODO use
-pub fn grid_iter(dim: [usize; 2]) -> impl Iterator<Item = [usize; 2]> + Clone + Send + Sync {
-    let mut xcounter = 0;
-    let mut ycounter = 0;
-    core::iter::from_fn(move || {
-        if ycounter >= dim[1] {
-            None
-        } else {
-            if xcounter >= dim[0] {
-                xcounter = 0;
-                ycounter += 1;
-            }
+///Same as [`grid_rect_iter`] but each point is moved randomly within its grid cell.
+///A `jitter` of zero gives the regular grid, and a `jitter` of one
+///places each point anywhere within its cell.
+pub fn jittered_grid_rect_iter<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+    jitter: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + Clone {
+    jittered_grid_rect_iter_from_rng(num_bots, rect, jitter, rand::thread_rng())
+}
 
-            let c = [xcounter, ycounter];
+///Same as [`jittered_grid_rect_iter`] but reproducible from a seed.
+pub fn jittered_grid_rect_iter_seeded<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+    jitter: f64,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + Clone + Send + Sync {
+    jittered_grid_rect_iter_from_rng(num_bots, rect, jitter, seeded_rng(seed))
+}
 
-            xcounter += 1;
+///Same as [`jittered_grid_rect_iter`] but draws from the provided rng.
+pub fn jittered_grid_rect_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    num_bots: usize,
+    rect: Rect<T>,
+    jitter: f64,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + ExactSizeIterator + Clone {
+    assert!(
+        (0.0..=1.0).contains(&jitter),
+        "jitter must be between zero and one"
+    );
 
-            Some(c)
-        }
-    })
+    let (num_bots, grid_dim, spacing) = match grid::grid_layout(num_bots, coord::rect_to_f64(rect))
+    {
+        Ok((grid_dim, spacing)) => (num_bots, grid_dim, spacing),
+        Err(_) => (0, [0, 0], vec2(0.0, 0.0)),
+    };
+
+    let topstart = vec2(rect.x.start.to_f64(), rect.y.start.to_f64());
+    let it = grid_iter(grid_dim).take(num_bots).map(move |[x, y]| {
+        let offset = vec2(unit_f32(&mut rng) as f64, unit_f32(&mut rng) as f64) * jitter;
+        let v = topstart + (vec2(x, y).inner_as() + offset).scale(spacing);
+        coord::point_from_f64([v.x, v.y])
+    });
+    DistIter::new(it).with_len(num_bots).with_rect(rect)
+}
+
+///Iterate over every cell of a grid in row major order.
+pub fn grid_iter(
+    dim: [usize; 2],
+) -> impl Dist<Item = [usize; 2], Num = usize>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + Clone
+       + Send
+       + Sync {
+    let [w, h] = dim;
+    DistIter::new((0..w * h).map(move |i| [i % w, i / w]))
+        .with_len(w * h)
+        .with_rect(Rect::new(0, w, 0, h))
 }
 
-pub fn fib_iter(point: [f64; 2], out_incr: f64) -> impl Iterator<Item = [f64; 2]> {
-    const PHI: f64 = 1.6180339887498948482;
+pub fn fib_iter<T: Coord>(point: [T; 2], out_incr: f64) -> impl Dist<Item = [T; 2], Num = T> {
+    let point = coord::point_to_f64(point);
+    const PHI: f64 = 1.618_033_988_749_895;
 
     //
     //     x        PHI
@@ -80,27 +182,31 @@ pub fn fib_iter(point: [f64; 2], out_incr: f64) -> impl Iterator<Item = [f64; 2]
     //const INCR:f64=PHI*std::f64::consts::TAU;
 
     let mut counter = 0;
-    core::iter::repeat_with(move || {
+    DistIter::new(core::iter::repeat_with(move || {
         let l = out_incr * (counter as f64).sqrt();
         let rad = (std::f64::consts::TAU / (PHI * PHI)) * (counter as f64);
         let x = point[0] + (rad.cos() * l);
         let y = point[1] + (rad.sin() * l);
         counter += 1;
-        [x, y]
-    })
+        coord::point_from_f64([x, y])
+    }))
 }
 
-pub fn spiral_iter(
-    point: [f64; 2],
+///An archimedean spiral that starts one unit from the point.
+///Points are only approximately evenly spaced, especially near the start.
+///See [`spiral::archimedean_iter`] for exact spacing.
+pub fn spiral_iter<T: Coord>(
+    point: [T; 2],
     circular_grow: f64,
     outward_grow: f64,
-) -> impl Iterator<Item = [f64; 2]> + Clone + FusedIterator {
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    let point = coord::point_to_f64(point);
     let start = 1.0;
     let rate = outward_grow;
     let mut rad = 0.0;
     let width = circular_grow;
 
-    core::iter::repeat_with(move || {
+    DistIter::new(core::iter::repeat_with(move || {
         let length = start + rate * rad;
 
         let x = point[0] + rad.cos() * length;
@@ -108,71 +214,335 @@ pub fn spiral_iter(
 
         rad += width / length;
 
-        [x, y]
+        coord::point_from_f64([x, y])
+    }))
+}
+
+///A sunflower of exactly `num` points that fills the disk of the given radius.
+///The first point is at the center and the last point lies on the boundary.
+///Panics if the radius is negative.
+pub fn fib_bounded_iter<T: Coord>(
+    center: [T; 2],
+    num: usize,
+    radius: f64,
+) -> impl Dist<Item = [T; 2], Num = T>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + FusedIterator
+       + Clone
+       + Send
+       + Sync {
+    const PHI: f64 = 1.618_033_988_749_895;
+    bounded_spiral(center, num, radius, move |i, _| {
+        (std::f64::consts::TAU / (PHI * PHI)) * (i as f64)
     })
 }
 
-/*
+///Same as [`fib_bounded_iter`] but fills the largest disk that fits in the rect.
+pub fn fib_rect_iter<T: Coord>(
+    num: usize,
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + FusedIterator
+       + Clone
+       + Send
+       + Sync {
+    let (center, radius) = inscribed_disk(rect);
+    fib_bounded_iter(center, num, radius)
+}
+
+///An archimedean spiral of exactly `num` points that fills the disk of the given radius.
+///The growth is solved for so that the distance between neighbouring points along
+///the spiral matches the distance between neighbouring turns.
+///The first point is at the center and the last point lies on the boundary.
+///Panics if the radius is negative.
+pub fn spiral_bounded_iter<T: Coord>(
+    center: [T; 2],
+    num: usize,
+    radius: f64,
+) -> impl Dist<Item = [T; 2], Num = T>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + FusedIterator
+       + Clone
+       + Send
+       + Sync {
+    //The spiral is r=b*theta, which has a length of about r^2/(2*b).
+    //Setting the spacing between turns (TAU*b) equal to the spacing along
+    //the spiral (length/(num-1)) and solving for b gives:
+    let b = radius / (2.0 * (std::f64::consts::PI * num.saturating_sub(1) as f64).sqrt());
+    bounded_spiral(
+        center,
+        num,
+        radius,
+        move |_, r| if b > 0.0 { r / b } else { 0.0 },
+    )
+}
+
+///Same as [`spiral_bounded_iter`] but fills the largest disk that fits in the rect.
+pub fn spiral_rect_iter<T: Coord>(
+    num: usize,
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + FusedIterator
+       + Clone
+       + Send
+       + Sync {
+    let (center, radius) = inscribed_disk(rect);
+    spiral_bounded_iter(center, num, radius)
+}
+
+fn inscribed_disk<T: Coord>(rect: Rect<T>) -> ([T; 2], f64) {
+    let rect = coord::rect_to_f64(rect);
+    let center = [
+        (rect.x.start + rect.x.end) / 2.0,
+        (rect.y.start + rect.y.end) / 2.0,
+    ];
+    let radius = (rect.x.end - rect.x.start).min(rect.y.end - rect.y.start) / 2.0;
+    (coord::point_from_f64(center), radius.max(0.0))
+}
+
+///Place point `i` at a distance of `radius*sqrt(i/(num-1))` from the center,
+///so that the points have a constant density over the disk.
+fn bounded_spiral<T: Coord>(
+    center: [T; 2],
+    num: usize,
+    radius: f64,
+    angle: impl Fn(usize, f64) -> f64 + Clone + Send + Sync,
+) -> DistIter<
+    T,
+    impl ExactSizeIterator<Item = [T; 2]> + DoubleEndedIterator + FusedIterator + Clone + Send + Sync,
+> {
+    assert!(radius >= 0.0, "radius must not be negative");
+
+    let c = coord::point_to_f64(center);
+    let last = num.saturating_sub(1).max(1) as f64;
+    let rect = Rect::new(c[0] - radius, c[0] + radius, c[1] - radius, c[1] + radius);
+
+    DistIter::new((0..num).map(move |i| {
+        let r = radius * (i as f64 / last).sqrt();
+        let (sin, cos) = angle(i, r).sin_cos();
+        coord::point_from_f64([c[0] + cos * r, c[1] + sin * r])
+    }))
+    .with_len(num)
+    .with_rect(rect.cast_coord())
+}
+
 ///Every distribution implements this.
-pub trait Dist<K>:Iterator<Item=Vec2<K>>+FusedIterator{}
-*/
+pub trait Dist: Iterator {
+    ///The coordinate type of the distribution.
+    type Num;
+
+    ///The exact number of items left, if the distribution is finite and the count is known.
+    fn exact_len(&self) -> Option<usize>;
 
-/*
-use core::marker::PhantomData;
+    ///A rect that contains every item, if known.
+    ///The rect is inclusive, so items may lie on its boundary.
+    fn bounding_rect(&self) -> Option<Rect<Self::Num>>;
+}
+
+///Attaches an optional count and bounding rect to an iterator so that it implements [`Dist`].
+#[derive(Clone)]
+pub struct DistIter<K, I> {
+    inner: I,
+    len: Option<usize>,
+    rect: Option<Rect<K>>,
+}
 
-pub struct ConstantAabbAdapter<K,I>{
-    a:I,
-    _p:PhantomData<K>,
-    radius:K
+impl<K, I> DistIter<K, I> {
+    pub fn new(inner: I) -> Self {
+        DistIter {
+            inner,
+            len: None,
+            rect: None,
+        }
+    }
+    ///The caller must ensure that the inner iterator yields exactly `len` items.
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+    pub fn with_rect(mut self, rect: Rect<K>) -> Self {
+        self.rect = Some(rect);
+        self
+    }
 }
 
-impl<K,I> ConstantAabbAdapter<K,I>{
-    pub fn new(radius:K,a:I)->Self{
-        ConstantAabbAdapter{a,radius,_p:PhantomData}
+impl<K, I: Iterator> Iterator for DistIter<K, I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        let a = self.inner.next();
+        if let (Some(len), Some(_)) = (&mut self.len, &a) {
+            *len -= 1;
+        }
+        a
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.len {
+            Some(len) => (len, Some(len)),
+            None => self.inner.size_hint(),
+        }
     }
 }
 
-impl<K:Clone,I:Clone> Clone for ConstantAabbAdapter<K,I>{
-    fn clone(&self)->Self{
-        ConstantAabbAdapter::new(self.radius.clone(),self.a.clone())
+impl<K, I: DoubleEndedIterator> DoubleEndedIterator for DistIter<K, I> {
+    fn next_back(&mut self) -> Option<I::Item> {
+        let a = self.inner.next_back();
+        if let (Some(len), Some(_)) = (&mut self.len, &a) {
+            *len -= 1;
+        }
+        a
     }
 }
 
-use core::ops::*;
+impl<K, I: ExactSizeIterator> ExactSizeIterator for DistIter<K, I> {}
+
+impl<K, I: FusedIterator> FusedIterator for DistIter<K, I> {}
 
-impl<K:Add<Output=K>+Sub<Output=K>+Copy,I:Iterator<Item=Vec2<K>>> Iterator for ConstantAabbAdapter<K,I>{
-    type Item=Rect<K>;
-    fn next(&mut self)->Option<Self::Item>{
-        self.a.next().map(|a|{
-            let r=self.radius;
-            Rect::new(a.x-r,a.x+r,a.y-r,a.y+r)
-        })
+impl<K: Copy, I: Iterator> Dist for DistIter<K, I> {
+    type Num = K;
+    fn exact_len(&self) -> Option<usize> {
+        self.len
+    }
+    fn bounding_rect(&self) -> Option<Rect<K>> {
+        self.rect
     }
 }
-*/
 
-pub fn rand2_iter(rect: Rect<f32>) -> impl Iterator<Item = [f32; 2]> + FusedIterator + Clone {
-    rand_iter(rect.x.start, rect.x.end)
-        .zip(rand_iter(rect.y.start, rect.y.end))
-        .map(|(x, y)| [x, y])
+///The random number generator used by all of the seeded distributions.
+///
+///This is a PCG32 generator, which has a portable, documented output stream,
+///so the same seed produces the same sequence on every platform.
+pub type SeededRng = rand_pcg::Pcg32;
+
+///Create a [`SeededRng`] from a seed.
+pub fn seeded_rng(seed: u64) -> SeededRng {
+    SeededRng::seed_from_u64(seed)
 }
 
-pub fn rand_iter(min: f32, max: f32) -> impl Iterator<Item = f32> + FusedIterator + Clone {
-    let mut rng = rand::thread_rng();
+///Generates a float in `[0,1)` from the top 24 bits of a `u32`.
+///We don't use `rng.gen::<f32>()` so that the mapping
+///from the rng stream to floats cannot change between versions of rand.
+pub(crate) fn unit_f32<R: RngCore>(rng: &mut R) -> f32 {
+    (rng.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+}
+
+pub fn rand2_iter<T: Coord>(
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    DistIter::new(
+        rand_iter(rect.x.start, rect.x.end)
+            .zip(rand_iter(rect.y.start, rect.y.end))
+            .map(|(x, y)| [x, y]),
+    )
+    .with_rect(rect)
+}
+
+///Same as [`rand2_iter`] but reproducible from a seed.
+pub fn rand2_iter_seeded<T: Coord>(
+    rect: Rect<T>,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    rand2_iter_from_rng(rect, seeded_rng(seed))
+}
+
+///Same as [`rand2_iter`] but draws from the provided rng.
+///The x and y coordinates are drawn one after the other from the same rng.
+pub fn rand2_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    rect: Rect<T>,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    DistIter::new(core::iter::repeat_with(move || {
+        let x = rand_between(rect.x.start, rect.x.end, &mut rng);
+        let y = rand_between(rect.y.start, rect.y.end, &mut rng);
+        [x, y]
+    }))
+    .with_rect(rect)
+}
+
+pub fn rand_iter<T: Coord>(min: T, max: T) -> impl FusedIterator<Item = T> + Clone {
+    rand_iter_from_rng(min, max, rand::thread_rng())
+}
+
+///Same as [`rand_iter`] but reproducible from a seed.
+pub fn rand_iter_seeded<T: Coord>(
+    min: T,
+    max: T,
+    seed: u64,
+) -> impl FusedIterator<Item = T> + Clone {
+    rand_iter_from_rng(min, max, seeded_rng(seed))
+}
 
-    core::iter::repeat_with(move || min + rng.gen::<f32>() * (max - min))
+///Same as [`rand_iter`] but draws from the provided rng.
+pub fn rand_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    min: T,
+    max: T,
+    mut rng: R,
+) -> impl FusedIterator<Item = T> + Clone {
+    core::iter::repeat_with(move || rand_between(min, max, &mut rng))
+}
+
+///Uniformly picks a value in `[min,max)`.
+pub(crate) fn rand_between<T: Coord, R: RngCore>(min: T, max: T, rng: &mut R) -> T {
+    T::lerp(min, max, unit_f32(rng) as f64)
+}
+
+///Poisson disk (blue noise) sampling using Bridson's algorithm.
+///Yields points inside the rect such that no two points are closer than `radius`.
+///The iterator ends once the rect has been filled.
+///
+///A lookup grid with one cell per `radius/sqrt(2)` square is allocated up front,
+///so memory use grows with the area of the rect divided by `radius` squared.
+///A tiny radius over a large rect can exhaust memory.
+pub fn poisson_iter<T: Coord>(
+    rect: Rect<T>,
+    radius: T,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    poisson_iter_from_rng(rect, radius, rand::thread_rng())
+}
+
+///Same as [`poisson_iter`] but reproducible from a seed.
+pub fn poisson_iter_seeded<T: Coord>(
+    rect: Rect<T>,
+    radius: T,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    poisson_iter_from_rng(rect, radius, seeded_rng(seed))
+}
+
+///Same as [`poisson_iter`] but draws from the provided rng.
+pub fn poisson_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    rect: Rect<T>,
+    radius: T,
+    rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    poisson::PoissonDisk::new(rect, radius, rng)
 }
 
 ///Randomly generates radiuses.
-pub struct RadiusGen {
+#[derive(Clone)]
+pub struct RadiusGen<R = ThreadRng> {
     min: Vec2<f32>,
     max: Vec2<f32>,
-    rng: ThreadRng,
+    rng: R,
 }
 impl RadiusGen {
     #[deprecated(since = "0.3.1", note = "use rand_iter() instead")]
     pub fn new(min_radius: Vec2<f32>, max_radius: Vec2<f32>) -> RadiusGen {
-        let rng = rand::thread_rng();
+        RadiusGen::with_rng(min_radius, max_radius, rand::thread_rng())
+    }
+}
+impl RadiusGen<SeededRng> {
+    pub fn with_seed(min_radius: Vec2<f32>, max_radius: Vec2<f32>, seed: u64) -> Self {
+        RadiusGen::with_rng(min_radius, max_radius, seeded_rng(seed))
+    }
+}
+impl<R: RngCore> RadiusGen<R> {
+    pub fn with_rng(min_radius: Vec2<f32>, max_radius: Vec2<f32>, rng: R) -> Self {
         RadiusGen {
             min: min_radius,
             max: max_radius,
@@ -180,327 +550,193 @@ impl RadiusGen {
         }
     }
 }
-impl Iterator for RadiusGen {
+impl<R: RngCore> Iterator for RadiusGen<R> {
     type Item = Vec2<f32>;
     fn next(&mut self) -> Option<Vec2<f32>> {
-        let x = self.min.x + self.rng.gen::<f32>() * (self.max.x - self.min.x);
-        let y = self.min.y + self.rng.gen::<f32>() * (self.max.y - self.min.y);
+        let x = self.min.x + unit_f32(&mut self.rng) * (self.max.x - self.min.x);
+        let y = self.min.y + unit_f32(&mut self.rng) * (self.max.y - self.min.y);
         Some(vec2(x, y))
     }
 }
-impl FusedIterator for RadiusGen {}
-
-///A wrapper around a RadiusGen that produced integers
-pub struct RadiusGenInt(RadiusGen);
+impl<R: RngCore> FusedIterator for RadiusGen<R> {}
+
+///A wrapper around a RadiusGen that produces integers.
+///
+///The radii are rounded down like every other [`Coord`] conversion.
+///Before 0.5 they were truncated towards zero, which only differs for negative radii.
+#[derive(Clone)]
+pub struct RadiusGenInt<R = ThreadRng>(RadiusGen<R>);
 impl RadiusGenInt {
     pub fn new(min_radius: Vec2<i32>, max_radius: Vec2<i32>) -> RadiusGenInt {
-        let rng = rand::thread_rng();
-        RadiusGenInt(RadiusGen {
-            min: vec2(min_radius.x as f32, min_radius.y as f32),
-            max: vec2(max_radius.x as f32, max_radius.y as f32),
+        RadiusGenInt::with_rng(min_radius, max_radius, rand::thread_rng())
+    }
+}
+impl RadiusGenInt<SeededRng> {
+    pub fn with_seed(min_radius: Vec2<i32>, max_radius: Vec2<i32>, seed: u64) -> Self {
+        RadiusGenInt::with_rng(min_radius, max_radius, seeded_rng(seed))
+    }
+}
+impl<R: RngCore> RadiusGenInt<R> {
+    pub fn with_rng(min_radius: Vec2<i32>, max_radius: Vec2<i32>, rng: R) -> Self {
+        RadiusGenInt(RadiusGen::with_rng(
+            min_radius.cast_coord(),
+            max_radius.cast_coord(),
             rng,
-        })
+        ))
     }
 }
-impl Iterator for RadiusGenInt {
+impl<R: RngCore> Iterator for RadiusGenInt<R> {
     type Item = Vec2<i32>;
     fn next(&mut self) -> Option<Vec2<i32>> {
-        self.0.next().map(|a| vec2(a.x as i32, a.y as i32))
+        self.0.next().map(CastCoord::<i32>::cast_coord)
     }
 }
-impl FusedIterator for RadiusGenInt {}
+impl<R: RngCore> FusedIterator for RadiusGenInt<R> {}
 
 //TODO add more distributions.
-/*
-fn test_bot_layout(mut bots: Vec<BBox<isize, Bot>>) {
-    let mut control_result = {
-        let mut src: Vec<(usize, usize)> = Vec::new();
-
-        let control_bots = bots.clone();
-        for (i, el1) in control_bots.iter().enumerate() {
-            for el2 in control_bots[i + 1..].iter() {
-                let a = el1;
-                let b = el2;
-                let ax = (a.get().0).0.get_range2::<XAXISS>();
-                let ay = (a.get().0).0.get_range2::<YAXISS>();
-                let bx = (b.get().0).0.get_range2::<XAXISS>();
-                let by = (b.get().0).0.get_range2::<YAXISS>();
-
-                if ax.intersects(bx) && ay.intersects(by) {
-                    src.push(test_support::create_unordered(&a.val, &b.val));
-                }
-            }
-        }
-        src
-    };
 
-    let mut test_result = {
-        let mut src: Vec<(usize, usize)> = Vec::new();
+#[cfg(test)]
+mod tests {
+    //The golden tests pin the output of the seeded distributions.
+    //If any of them fail, the same seed no longer gives the same sequence.
+    use super::*;
+    use rand::RngCore;
 
-        {
-            let mut dyntree = DinoTree::new_seq(&mut bots,  StartAxis::Xaxis);
-
-            let clos = |a: ColSingle<BBox<isize, Bot>>, b: ColSingle<BBox<isize, Bot>>| {
-                //let (a,b)=(ca,ca.1);
-                //let a=ca[0];
-                //let b=ca[1];
-                src.push(test_support::create_unordered(&a.inner, &b.inner));
-            };
-
-            dyntree.intersect_every_pair_seq(clos);
-        }
-
-        src
-    };
-
-    control_result.sort_by(&test_support::compair_bot_pair);
-    test_result.sort_by(&test_support::compair_bot_pair);
-
-    println!(
-        "control length={} test length={}",
-        control_result.len(),
-        test_result.len()
-    );
-    {
-        use std::collections::HashSet;
-        println!(
-            "control vs test len={:?}",
-            (control_result.len(), test_result.len())
+    #[test]
+    fn seeded_rng_golden() {
+        let mut rng = seeded_rng(42);
+        assert_eq!(
+            [rng.next_u32(), rng.next_u32(), rng.next_u32()],
+            [3400036912, 3349765444, 515166382]
         );
-
-        let mut control_hash = HashSet::new();
-        for k in control_result.iter() {
-            control_hash.insert(k);
-        }
-
-        let mut test_hash = HashSet::new();
-        for k in test_result.iter() {
-            test_hash.insert(k);
-        }
-
-        let diff = control_hash
-            .symmetric_difference(&test_hash)
-            .collect::<Vec<_>>();
-
-        if diff.len() != 0 {
-            //println!("diff={:?}",diff);
-
-            //println!("first={:?}",(&bots[diff[0].0],&bots[diff[0].1]));
-            //let bots_copy = bots.clone();
-
-
-            let mut dyntree = DinoTree::new(&mut bots, StartAxis::Xaxis);
-
-            for i in diff.iter(){
-                let id1=i.0;
-                let id2=i.1;
-                println!("------------------");
-                println!("{:?}",dyntree.find_element(|bla|{bla.val.id==id1}));
-                println!("{:?}",dyntree.find_element(|bla|{bla.val.id==id2}));
-
-                println!("------------------");
-            }
-            //use compt::CTreeIterator;
-            /*
-            for i in diff.iter(){
-                let level=dyntree.0.get_level_desc();
-                let first={
-                  let dd=dyntree.0.get_iter_mut();
-                  let ll=compt::LevelIter::new(dd,level);
-                  let mut first=None;
-                  'bla:for (level,n) in ll.dfs_preorder_iter(){
-                     for bot in n.range.iter(){
-                        if bot.get().1.id==i.0{
-                          first=Some(level.get_depth());
-                          break 'bla;
-                        }
-                     }
-                  }
-                  first
-                };
-
-                let second={
-                  let dd=dyntree.0.get_iter_mut();
-                  let ll=compt::LevelIter::new(dd,level);
-
-                  let mut second=None;
-                  'bla2:for (level,n) in ll.dfs_preorder_iter(){
-                     for bot in n.range.iter(){
-                        if bot.get().1.id==i.1{
-                          second=Some(level.get_depth());
-                          break 'bla2;
-                        }
-                     }
-                  }
-                  second
-                };
-
-                println!("debug={:?}",(first,second));
-
-                let first_bot=bots_copy.iter().find(|a|a.get().1.id==i.0).unwrap();
-                let second_bot=bots_copy.iter().find(|a|a.get().1.id==i.1).unwrap();
-                println!("{:?}",(first_bot.get().0,second_bot.get().0));
-            }*/
-        }
-
-        assert!(diff.len() == 0);
     }
-}
-
-*/
 
-/*
-
-#[test]
-fn test_bounding_boxes_as_points() {
-
-
-    let mut bots=create_bots_isize(|id|Bot{id,col:Vec::new()},&[0,800,0,800],500,[2,3]);
-    /*
-    let spawn_world = make_rect((-990, 990), (-90, 90));
-
-    let mut p = PointGenerator::new(&spawn_world, &[1, 2, 3, 4, 5]);
-
-    let bots: Vec<BBox<isize, Bot>> = {
-        (0..2000)
-            .map(|id| {
-                let p=p.random_point();
-                let rect = AABBox::new((p.0,p.0),(p.1,p.1));
-                BBox::new(
-                    Bot {
-                        id,
-                        col: Vec::new(),
-                    },
-                    rect,
-                )
-            })
-            .collect()
-    };
-    */
-
-    test_bot_layout(bots);
+    #[test]
+    fn unit_f32_golden() {
+        let mut rng = seeded_rng(42);
+        assert_eq!(unit_f32(&mut rng), 0.7916328);
+        assert_eq!(unit_f32(&mut rng), 0.779928);
+    }
 
-}
-*/
+    #[test]
+    fn rand2_iter_seeded_golden() {
+        let a: Vec<[f32; 2]> = rand2_iter_seeded(rect(0.0, 1.0, 0.0, 1.0), 42)
+            .take(2)
+            .collect();
+        assert_eq!(a, [[0.7916328, 0.779928], [0.11994648, 0.98364145]]);
+
+        let a: Vec<[f64; 2]> = rand2_iter_seeded(rect(0.0, 1.0, 0.0, 1.0), 42)
+            .take(1)
+            .collect();
+        assert_eq!(a, [[0.7916327714920044, 0.7799280285835266]]);
+
+        let a: Vec<[i32; 2]> = rand2_iter_seeded(rect(0, 100, -50, 50), 42)
+            .take(2)
+            .collect();
+        assert_eq!(a, [[79, 27], [11, 48]]);
+    }
 
-/*
-///TODO
-pub mod russian_doll{
-    /*
+    #[test]
+    fn rand_iter_seeded_golden() {
+        let a: Vec<f32> = rand_iter_seeded(-1.0, 1.0, 7).take(3).collect();
+        assert_eq!(a, [0.13724875, -0.8071866, 0.27758837]);
+    }
 
-        #[test]
-        fn test_russian_doll() {
-            //In this test, test larger and larger rectangles overlapping each other.
+    #[test]
+    fn radius_gen_with_seed_golden() {
+        let a: Vec<_> = RadiusGen::with_seed(vec2(1.0, 2.0), vec2(3.0, 4.0), 42)
+            .take(2)
+            .collect();
+        assert_eq!(a, [vec2(2.5832655, 3.559856), vec2(1.239893, 3.9672828)]);
+
+        let a: Vec<_> = RadiusGenInt::with_seed(vec2(1, 2), vec2(30, 40), 42)
+            .take(2)
+            .collect();
+        assert_eq!(a, [vec2(23, 31), vec2(4, 39)]);
+    }
 
+    #[test]
+    fn uniform_rand_gen_with_seed_golden() {
+        let gen = uniform_rand::UniformRandGen::with_seed(rect(10.0, 20.0, -5.0, 5.0), 42);
 
-            let mut bots = Vec::new();
-            let mut id_counter = 0..;
+        let a: Vec<_> = gen.clone().take(2).collect();
+        assert_eq!(a, [vec2(17.916328, 2.7992802), vec2(11.199465, 4.8364143)]);
 
-            for x in (-1000..2000).step_by(20) {
-                for y in (-100..200).step_by(20) {
-                    if x > y {
-                        let id = id_counter.next().unwrap();
+        let a: Vec<_> = gen.with_int().take(2).collect();
+        assert_eq!(a, [vec2(17, 2), vec2(11, 4)]);
+    }
 
-                        //let rect = AABBox(make_rect((-1000, -100), (x, y)));
-                        let rect =AABBox::new((-1000,-100),(y,x));
+    fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+    }
 
-                        bots.push(BBox::new(
-                            Bot {
-                                id,
-                                col: Vec::new(),
-                            },
-                            rect,
-                        ));
-                    }
-                }
-            }
+    fn bounded(num: usize) -> [Vec<[f64; 2]>; 2] {
+        let center = [3.0, -1.0];
+        let fib = fib_bounded_iter(center, num, 5.0);
+        let spiral = spiral_bounded_iter(center, num, 5.0);
+        assert_eq!(fib.len(), num);
+        assert_eq!(spiral.len(), num);
+        [fib.collect(), spiral.collect()]
+    }
 
-            test_bot_layout(bots);
+    #[test]
+    fn bounded_spirals_fill_disk() {
+        let center = [3.0, -1.0];
+        for points in bounded(200) {
+            assert_eq!(points.len(), 200);
+            assert_eq!(points[0], center);
+            assert!((distance(points[199], center) - 5.0).abs() < 1e-9);
+            assert!(points.iter().all(|&p| distance(p, center) <= 5.0 + 1e-9));
+
+            //Constant density means that half of the points are within radius/sqrt(2).
+            let inner = points
+                .iter()
+                .filter(|&&p| distance(p, center) <= 5.0 / 2f64.sqrt())
+                .count();
+            assert!((99..=101).contains(&inner));
         }
+    }
 
-    */
-}
-
-
-///TODO
-pub mod one_apart{
-    /*
     #[test]
-    fn test_1_apart() {
-
-        let mut bots = Vec::new();
-        let mut id_counter = 0..;
-        for x in (-1000..2000).step_by(21) {
-            for y in (-100..200).step_by(21) {
-                let id = id_counter.next().unwrap();
-                //let rect = create_rect_from_point((x, y));
-                let rect =AABBox::new((x-10,x+10),(y-10,y+10));
-
-                bots.push(BBox::new(
-                    Bot {
-                        id,
-                        col: Vec::new(),
-                    },
-                    rect,
-                ));
-            }
+    fn bounded_spirals_few_points() {
+        let center = [3.0, -1.0];
+        for points in bounded(0) {
+            assert!(points.is_empty());
+        }
+        for points in bounded(1) {
+            assert_eq!(points, [center]);
+        }
+        for points in bounded(2) {
+            assert_eq!(points[0], center);
+            assert!((distance(points[1], center) - 5.0).abs() < 1e-9);
         }
 
-        test_bot_layout(bots);
+        let points: Vec<[f64; 2]> = fib_bounded_iter(center, 10, 0.0).collect();
+        assert!(points.iter().all(|&p| p == center));
     }
-    */
-
-}
 
-///TODO
-pub mod lattice{
-    /*
     #[test]
-    fn test_corners_touch() {
-
-        //# # # #
-        // # # #
-        //# # # #
-        let mut bots = Vec::new();
-        let mut id_counter = 0..;
-        let mut a = false;
-        for y in (-100..200).step_by(20) {
-            if a {
-                for x in (-1000..2000).step_by(20).step_by(2) {
-                    let id = id_counter.next().unwrap();
-                    let rect =AABBox::new((x-10,x+10),(y-10,y+10));
-                    bots.push(BBox::new(
-                        Bot {
-                            id,
-                            col: Vec::new(),
-                        },
-                        rect,
-                    ));
-                }
-            } else {
-                for x in (-1000..2000).step_by(20).skip(1).step_by(2) {
-                    let id = id_counter.next().unwrap();
-                    //let rect = create_rect_from_point((x, y));
-                    let rect =AABBox::new((x-10,x+10),(y-10,y+10));
-
-                    bots.push(BBox::new(
-                        Bot {
-                            id,
-                            col: Vec::new(),
-                        },
-                        rect,
-                    ));
-                }
-            }
-            a = !a;
+    fn rect_spirals_stay_inside() {
+        let r = rect(0.0, 20.0, 0.0, 10.0);
+        let fib = fib_rect_iter(300, r);
+        let spiral = spiral_rect_iter(300, r);
+        assert_eq!(fib.bounding_rect(), Some(rect(5.0, 15.0, 0.0, 10.0)));
+        for it in [fib.collect::<Vec<[f64; 2]>>(), spiral.collect()] {
+            assert_eq!(it.len(), 300);
+            assert_eq!(it[0], [10.0, 5.0]);
+            assert!(it.iter().all(|&p| distance(p, [10.0, 5.0]) <= 5.0 + 1e-9));
         }
-
-        test_bot_layout(bots);
-        //assert!(false);
     }
-    */
-}
-
 
+    #[test]
+    #[should_panic(expected = "radius must not be negative")]
+    fn fib_negative_radius() {
+        let _ = fib_bounded_iter([0.0, 0.0], 10, -1.0);
+    }
 
-*/
+    #[test]
+    #[should_panic(expected = "radius must not be negative")]
+    fn spiral_negative_radius() {
+        let _ = spiral_bounded_iter([0.0, 0.0], 10, -1.0);
+    }
+}
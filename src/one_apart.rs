@@ -0,0 +1,74 @@
+use axgeom::*;
+use core::ops::*;
+
+use crate::Dist;
+use crate::DistIter;
+
+///Generates a `dim[0]` by `dim[1]` grid of square AABBs of the given `radius`
+///starting with the first AABB centered at `start`.
+///Neighbouring AABBs are separated by `gap`.
+///
+///A positive gap means neighbours almost touch but never intersect.
+///A gap of zero means neighbours exactly touch, and a negative gap means they overlap.
+///Works with both integer and float coordinates.
+pub fn grid_iter<T>(
+    start: [T; 2],
+    dim: [usize; 2],
+    radius: T,
+    gap: T,
+) -> impl Dist<Item = Rect<T>, Num = T> + Clone + Send + Sync
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Send + Sync,
+{
+    let spacing = radius + radius + gap;
+    let steps = move |start: T, num: usize| {
+        core::iter::successors(Some(start), move |&a| Some(a + spacing)).take(num)
+    };
+
+    let aabb = move |x: T, y: T| Rect::from_point(vec2(x, y), vec2same(radius));
+
+    let it =
+        steps(start[1], dim[1]).flat_map(move |y| steps(start[0], dim[0]).map(move |x| aabb(x, y)));
+
+    let len = dim[0] * dim[1];
+    let it = DistIter::new(it).with_len(len);
+    if len == 0 {
+        return it;
+    }
+
+    let last_x = steps(start[0], dim[0]).last().unwrap();
+    let last_y = steps(start[1], dim[1]).last().unwrap();
+    let mut rect = aabb(start[0], start[1]);
+    rect.grow_to_fit(&aabb(last_x, last_y));
+    it.with_rect(rect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::intersecting_pairs;
+
+    fn num_pairs<T: PartialOrd + Copy>(it: impl Iterator<Item = Rect<T>>) -> usize {
+        intersecting_pairs(&it.collect::<Vec<_>>()).len()
+    }
+
+    #[test]
+    fn gap_decides_intersections() {
+        //In a 4x4 grid there are 12 horizontal, 12 vertical and 18 diagonal neighbours.
+        assert_eq!(num_pairs(grid_iter([0, 0], [4, 4], 5, 1)), 0);
+        assert_eq!(num_pairs(grid_iter([0, 0], [4, 4], 5, 0)), 42);
+        assert_eq!(num_pairs(grid_iter([0, 0], [4, 4], 5, -1)), 42);
+
+        assert_eq!(num_pairs(grid_iter([0.0, 0.0], [4, 4], 5.0, 0.5)), 0);
+        assert_eq!(num_pairs(grid_iter([0.0, 0.0], [4, 4], 5.0, 0.0)), 42);
+        assert_eq!(num_pairs(grid_iter([0.0, 0.0], [4, 4], 5.0, -0.5)), 42);
+    }
+
+    #[test]
+    fn len_and_bounds() {
+        let it = grid_iter([10, 20], [3, 2], 5, 1);
+        assert_eq!(it.exact_len(), Some(6));
+        assert_eq!(it.bounding_rect(), Some(rect(5, 37, 15, 36)));
+        assert_eq!(grid_iter([0, 0], [0, 3], 5, 1).bounding_rect(), None);
+    }
+}
@@ -0,0 +1,177 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use rand::RngCore;
+
+use crate::coord::*;
+use crate::rand_between;
+use crate::unit_f32;
+use crate::Dist;
+
+///How many candidates to try around an active sample before giving up on it.
+const NUM_CANDIDATES: usize = 30;
+
+///Bridson's algorithm for poisson disk sampling.
+///Points are yielded as soon as they are accepted.
+///
+///Sampling is done in f64. Integer coordinates are rounded down,
+///so their separation may be up to one unit short on each axis.
+#[derive(Clone)]
+pub(crate) struct PoissonDisk<T, R> {
+    bounds: Rect<T>,
+    rect: Rect<f64>,
+    radius: f64,
+    cell: f64,
+    grid_dim: [usize; 2],
+    grid: Vec<Option<usize>>,
+    samples: Vec<[f64; 2]>,
+    active: Vec<usize>,
+    rng: R,
+}
+
+impl<T: Coord, R: RngCore> PoissonDisk<T, R> {
+    pub(crate) fn new(bounds: Rect<T>, radius: T, rng: R) -> Self {
+        let rect = rect_to_f64(bounds);
+        let radius = radius.to_f64();
+        assert!(radius > 0.0, "radius must be positive");
+
+        let width = rect.x.end - rect.x.start;
+        let height = rect.y.end - rect.y.start;
+        assert!(width >= 0.0 && height >= 0.0, "rect must not be inverted");
+
+        //Each cell can contain at most one sample.
+        let cell = radius / core::f64::consts::SQRT_2;
+        let grid_dim = [
+            ((width / cell).ceil() as usize).max(1),
+            ((height / cell).ceil() as usize).max(1),
+        ];
+
+        let num_cells = grid_dim[0]
+            .checked_mul(grid_dim[1])
+            .expect("radius is too small for the rect");
+
+        PoissonDisk {
+            bounds,
+            rect,
+            radius,
+            cell,
+            grid_dim,
+            grid: vec![None; num_cells],
+            samples: Vec::new(),
+            active: Vec::new(),
+            rng,
+        }
+    }
+
+    fn cell_of(&self, p: [f64; 2]) -> [usize; 2] {
+        let x = ((p[0] - self.rect.x.start) / self.cell) as usize;
+        let y = ((p[1] - self.rect.y.start) / self.cell) as usize;
+        [x.min(self.grid_dim[0] - 1), y.min(self.grid_dim[1] - 1)]
+    }
+
+    fn in_rect(&self, p: [f64; 2]) -> bool {
+        p[0] >= self.rect.x.start
+            && p[0] < self.rect.x.end
+            && p[1] >= self.rect.y.start
+            && p[1] < self.rect.y.end
+    }
+
+    fn is_far_enough(&self, p: [f64; 2]) -> bool {
+        let [cx, cy] = self.cell_of(p);
+        let r2 = self.radius * self.radius;
+
+        let xs = cx.saturating_sub(2)..(cx + 3).min(self.grid_dim[0]);
+        for y in cy.saturating_sub(2)..(cy + 3).min(self.grid_dim[1]) {
+            for x in xs.clone() {
+                if let Some(i) = self.grid[y * self.grid_dim[0] + x] {
+                    let o = self.samples[i];
+                    let dx = o[0] - p[0];
+                    let dy = o[1] - p[1];
+                    if dx * dx + dy * dy < r2 {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    fn insert(&mut self, p: [f64; 2]) -> [f64; 2] {
+        let [cx, cy] = self.cell_of(p);
+        let i = self.samples.len();
+        self.grid[cy * self.grid_dim[0] + cx] = Some(i);
+        self.samples.push(p);
+        self.active.push(i);
+        p
+    }
+}
+
+impl<T: Coord, R: RngCore> FusedIterator for PoissonDisk<T, R> {}
+
+impl<T: Coord, R: RngCore> Iterator for PoissonDisk<T, R> {
+    type Item = [T; 2];
+    fn next(&mut self) -> Option<[T; 2]> {
+        if self.samples.is_empty() {
+            if self.rect.x.start >= self.rect.x.end || self.rect.y.start >= self.rect.y.end {
+                return None;
+            }
+            let x = rand_between(self.rect.x.start, self.rect.x.end, &mut self.rng);
+            let y = rand_between(self.rect.y.start, self.rect.y.end, &mut self.rng);
+            return Some(point_from_f64(self.insert([x, y])));
+        }
+
+        while !self.active.is_empty() {
+            let k = ((self.rng.next_u32() as u64 * self.active.len() as u64) >> 32) as usize;
+            let center = self.samples[self.active[k]];
+
+            for _ in 0..NUM_CANDIDATES {
+                //Uniform by area in the annulus between radius and 2*radius.
+                let angle = unit_f32(&mut self.rng) as f64 * core::f64::consts::TAU;
+                let dis = self.radius * (1.0 + 3.0 * unit_f32(&mut self.rng) as f64).sqrt();
+                let p = [center[0] + angle.cos() * dis, center[1] + angle.sin() * dis];
+
+                if self.in_rect(p) && self.is_far_enough(p) {
+                    return Some(point_from_f64(self.insert(p)));
+                }
+            }
+
+            self.active.swap_remove(k);
+        }
+        None
+    }
+}
+
+impl<T: Coord, R: RngCore> Dist for PoissonDisk<T, R> {
+    type Num = T;
+    fn exact_len(&self) -> Option<usize> {
+        None
+    }
+    fn bounding_rect(&self) -> Option<Rect<T>> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axgeom::*;
+
+    #[test]
+    fn points_are_apart_and_inside() {
+        let bounds = rect(-10.0, 30.0, 5.0, 25.0);
+        let radius = 1.5;
+        let points: Vec<[f64; 2]> = crate::poisson_iter_seeded(bounds, radius, 3).collect();
+        assert!(points.len() > 50);
+
+        for p in points.iter() {
+            assert!(bounds.x.start <= p[0] && p[0] < bounds.x.end);
+            assert!(bounds.y.start <= p[1] && p[1] < bounds.y.end);
+        }
+
+        let mut min = f64::MAX;
+        for (i, a) in points.iter().enumerate() {
+            for b in points[i + 1..].iter() {
+                min = min.min(((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt());
+            }
+        }
+        assert!(min >= radius, "{} is closer than {}", min, radius);
+    }
+}
@@ -0,0 +1,521 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use rand::RngCore;
+use std::sync::Arc;
+
+use crate::coord::*;
+use crate::seeded_rng;
+use crate::unit_f32;
+use crate::Dist;
+use crate::DistIter;
+
+type Point = [f64; 2];
+
+///Why a polygon could not be triangulated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PolygonError {
+    ///A ring has fewer than three vertices.
+    TooFewVertices,
+    ///The polygon has no area.
+    ZeroArea,
+    ///The polygon intersects itself, or could not be triangulated.
+    TriangulationFailed,
+}
+
+impl core::fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            PolygonError::TooFewVertices => write!(f, "a ring has fewer than three vertices"),
+            PolygonError::ZeroArea => write!(f, "the polygon has no area"),
+            PolygonError::TriangulationFailed => {
+                write!(
+                    f,
+                    "the polygon intersects itself or could not be triangulated"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
+
+///A simple polygon, possibly non-convex and with holes,
+///that has been triangulated so that points can be sampled in it.
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    rings: Vec<Vec<Point>>,
+    triangles: Vec<[Point; 3]>,
+    //Running total of the triangle areas.
+    cumulative_area: Vec<f64>,
+    bounds: Rect<f64>,
+}
+
+fn cross(o: Point, a: Point, b: Point) -> f64 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+///Twice the signed area, positive if counter clockwise.
+fn signed_area2(ring: &[Point]) -> f64 {
+    let mut a = 0.0;
+    for (i, p) in ring.iter().enumerate() {
+        let q = ring[(i + 1) % ring.len()];
+        a += p[0] * q[1] - q[0] * p[1];
+    }
+    a
+}
+
+///Whether the segments ab and cd properly cross each other.
+fn segments_cross(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+    ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+}
+
+///Whether the ring vertex `p`, with neighbours `prev` and `next`,
+///reaches into the interior of the counter clockwise triangle.
+///A vertex that only touches the boundary of the triangle does not, unless
+///one of its edges leaves it towards the interior.
+fn in_triangle(p: Point, [prev, next]: [Point; 2], tri: [Point; 3]) -> bool {
+    let sides = [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])];
+    if sides.iter().any(|&(a, b)| cross(a, b, p) < 0.0) {
+        return false;
+    }
+    //Along a side that `p` lies on, the edge to `q` must point inwards.
+    let enters = |q: Point| {
+        sides
+            .iter()
+            .all(|&(a, b)| cross(a, b, p) > 0.0 || cross(a, b, q) > 0.0)
+    };
+    enters(prev) || enters(next)
+}
+
+///Whether the segment from `a` to `b` does not cross any edge of the rings
+///and does not pass through any of their vertices.
+fn is_visible(a: Point, b: Point, rings: &[&[Point]]) -> bool {
+    let on_segment = |p: Point| {
+        cross(a, b, p) == 0.0 && (p[0] - a[0]) * (p[0] - b[0]) + (p[1] - a[1]) * (p[1] - b[1]) < 0.0
+    };
+    rings.iter().all(|ring| {
+        (0..ring.len()).all(|i| {
+            let c = ring[i];
+            let d = ring[(i + 1) % ring.len()];
+            //Edges that share an endpoint with the segment can not cross it.
+            !on_segment(c) && (c == a || c == b || d == a || d == b || !segments_cross(a, b, c, d))
+        })
+    })
+}
+
+///Whether the segment from the ring vertex `v` towards `q` starts out inside of the polygon.
+///The interior lies to the left of the edges `prev -> v` and `v -> next`.
+fn locally_inside(v: Point, [prev, next]: [Point; 2], q: Point) -> bool {
+    if cross(prev, v, next) > 0.0 {
+        cross(prev, v, q) > 0.0 && cross(v, next, q) > 0.0
+    } else {
+        cross(prev, v, q) > 0.0 || cross(v, next, q) > 0.0
+    }
+}
+
+///Whether any two edges of the rings cross each other.
+fn has_crossing(rings: &[&[Point]]) -> bool {
+    let edges: Vec<(Point, Point)> = rings
+        .iter()
+        .flat_map(|ring| (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()])))
+        .collect();
+    edges.iter().enumerate().any(|(i, &(a, b))| {
+        edges[i + 1..]
+            .iter()
+            .any(|&(c, d)| segments_cross(a, b, c, d))
+    })
+}
+
+///Connect every hole to the outer ring with a bridge,
+///resulting in one ring that can be ear clipped.
+///The outer ring must be counter clockwise and the holes clockwise.
+fn merge_holes(outer: &[Point], holes: &[Vec<Point>]) -> Vec<Point> {
+    let mut merged = outer.to_vec();
+
+    //Merge the holes from right to left, so that the bridge of a hole
+    //can not be blocked by a hole that has not been merged yet.
+    let mut holes: Vec<&Vec<Point>> = holes.iter().collect();
+    holes.sort_by(|a, b| max_x(b).partial_cmp(&max_x(a)).unwrap());
+
+    for (k, hole) in holes.iter().enumerate() {
+        let mut others: Vec<&[Point]> = holes[k..].iter().map(|h| h.as_slice()).collect();
+        others.push(&merged);
+
+        //Try the rightmost vertices of the hole first, bridging each
+        //to the closest vertex of the merged ring that it can see.
+        let mut starts: Vec<usize> = (0..hole.len()).collect();
+        starts.sort_by(|&a, &b| hole[b][0].partial_cmp(&hole[a][0]).unwrap());
+
+        let n = merged.len();
+        let h = hole.len();
+        let dis = |m: Point, i: usize| {
+            let p = merged[i];
+            (p[0] - m[0]).powi(2) + (p[1] - m[1]).powi(2)
+        };
+        let bridges = starts.iter().flat_map(|&m_index| {
+            let m = hole[m_index];
+            let mut candidates: Vec<usize> = (0..n).collect();
+            candidates.sort_by(|&a, &b| dis(m, a).partial_cmp(&dis(m, b)).unwrap());
+            candidates.into_iter().map(move |i| (m_index, i))
+        });
+        let (m_index, bridge) = bridges
+            .clone()
+            .find(|&(m_index, i)| {
+                let m = hole[m_index];
+                let p = merged[i];
+                let m_neighbours = [hole[(m_index + h - 1) % h], hole[(m_index + 1) % h]];
+                let p_neighbours = [merged[(i + n - 1) % n], merged[(i + 1) % n]];
+                locally_inside(m, m_neighbours, p)
+                    && locally_inside(p, p_neighbours, m)
+                    && is_visible(m, p, &others)
+            })
+            .unwrap_or_else(|| bridges.clone().next().unwrap());
+
+        let p = merged[bridge];
+        let mut next = Vec::with_capacity(merged.len() + hole.len() + 2);
+        next.extend_from_slice(&merged[..=bridge]);
+        next.extend((0..=hole.len()).map(|i| hole[(m_index + i) % hole.len()]));
+        next.push(p);
+        next.extend_from_slice(&merged[bridge + 1..]);
+        merged = next;
+    }
+    merged
+}
+
+fn max_x(ring: &[Point]) -> f64 {
+    ring.iter().map(|p| p[0]).fold(f64::NEG_INFINITY, f64::max)
+}
+
+///Ear clipping triangulation of a counter clockwise ring.
+fn ear_clip(ring: Vec<Point>) -> Result<Vec<[Point; 3]>, PolygonError> {
+    let mut ring = ring;
+    let mut triangles = Vec::with_capacity(ring.len());
+
+    let mut i = 0;
+    let mut since_last_ear = 0;
+    while ring.len() > 3 {
+        if since_last_ear > ring.len() {
+            return Err(PolygonError::TriangulationFailed);
+        }
+
+        let n = ring.len();
+        let prev = ring[(i + n - 1) % n];
+        let cur = ring[i % n];
+        let next = ring[(i + 1) % n];
+        let c = cross(prev, cur, next);
+
+        let is_ear = if c == 0.0 {
+            //Collinear vertices and the tips of zero width spikes can be removed
+            //without adding a triangle. The end of a bridge that lies on a straight
+            //edge is kept, since it is where the ring touches itself.
+            let turns_back = (cur[0] - prev[0]) * (next[0] - cur[0])
+                + (cur[1] - prev[1]) * (next[1] - cur[1])
+                <= 0.0;
+            turns_back || ring.iter().filter(|&&p| p == cur).count() == 1
+        } else if c < 0.0 {
+            false
+        } else {
+            let tri = [prev, cur, next];
+            (0..n).all(|j| {
+                let p = ring[j];
+                let neighbours = [ring[(j + n - 1) % n], ring[(j + 1) % n]];
+                p == prev || p == cur || p == next || !in_triangle(p, neighbours, tri)
+            })
+        };
+
+        if is_ear {
+            if c > 0.0 {
+                triangles.push([prev, cur, next]);
+            }
+            ring.remove(i % n);
+            since_last_ear = 0;
+        } else {
+            i += 1;
+            since_last_ear += 1;
+        }
+        i %= ring.len();
+    }
+    if cross(ring[0], ring[1], ring[2]) > 0.0 {
+        triangles.push([ring[0], ring[1], ring[2]]);
+    }
+    Ok(triangles)
+}
+
+impl Polygon {
+    ///Create a polygon from its vertices, in either winding order.
+    pub fn new(outer: Vec<[f64; 2]>) -> Result<Polygon, PolygonError> {
+        Polygon::with_holes(outer, Vec::new())
+    }
+
+    ///Create a polygon with holes. The holes must be inside of the outer ring
+    ///and must not overlap each other. Any winding order is accepted.
+    pub fn with_holes(
+        mut outer: Vec<[f64; 2]>,
+        mut holes: Vec<Vec<[f64; 2]>>,
+    ) -> Result<Polygon, PolygonError> {
+        if outer.len() < 3 || holes.iter().any(|h| h.len() < 3) {
+            return Err(PolygonError::TooFewVertices);
+        }
+
+        if signed_area2(&outer) < 0.0 {
+            outer.reverse();
+        }
+        for hole in holes.iter_mut() {
+            if signed_area2(hole) > 0.0 {
+                hole.reverse();
+            }
+        }
+
+        let mut rings: Vec<&[Point]> = vec![&outer];
+        rings.extend(holes.iter().map(|h| h.as_slice()));
+        if has_crossing(&rings) {
+            return Err(PolygonError::TriangulationFailed);
+        }
+
+        let triangles = ear_clip(merge_holes(&outer, &holes))?;
+
+        let mut total = 0.0;
+        let cumulative_area: Vec<f64> = triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                total += cross(a, b, c) / 2.0;
+                total
+            })
+            .collect();
+        if total.is_nan() || total <= 0.0 {
+            return Err(PolygonError::ZeroArea);
+        }
+
+        let mut bounds = Rect::new(outer[0][0], outer[0][0], outer[0][1], outer[0][1]);
+        for p in outer.iter() {
+            bounds.grow_to_fit_point(vec2(p[0], p[1]));
+        }
+
+        let mut rings = vec![outer];
+        rings.extend(holes);
+        Ok(Polygon {
+            rings,
+            triangles,
+            cumulative_area,
+            bounds,
+        })
+    }
+
+    pub fn area(&self) -> f64 {
+        *self.cumulative_area.last().unwrap()
+    }
+
+    pub fn triangles(&self) -> &[[[f64; 2]; 3]] {
+        &self.triangles
+    }
+
+    pub fn bounding_rect(&self) -> Rect<f64> {
+        self.bounds
+    }
+
+    ///Whether the point is inside of the polygon and not inside of any of its holes.
+    pub fn contains_point(&self, p: [f64; 2]) -> bool {
+        //even-odd rule over every ring
+        let mut inside = false;
+        for ring in self.rings.iter() {
+            for i in 0..ring.len() {
+                let a = ring[i];
+                let b = ring[(i + 1) % ring.len()];
+                if (a[1] > p[1]) != (b[1] > p[1])
+                    && p[0] < a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+                {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    ///Uniformly sample a point in the polygon.
+    pub fn sample<R: RngCore>(&self, rng: &mut R) -> [f64; 2] {
+        let t = unit_f32(rng) as f64 * self.area();
+        let i = self
+            .cumulative_area
+            .partition_point(|&c| c <= t)
+            .min(self.triangles.len() - 1);
+        let [a, b, c] = self.triangles[i];
+
+        //Uniform in a triangle by folding the unit square in half.
+        let mut u = unit_f32(rng) as f64;
+        let mut v = unit_f32(rng) as f64;
+        if u + v > 1.0 {
+            u = 1.0 - u;
+            v = 1.0 - v;
+        }
+        [
+            a[0] + u * (b[0] - a[0]) + v * (c[0] - a[0]),
+            a[1] + u * (b[1] - a[1]) + v * (c[1] - a[1]),
+        ]
+    }
+}
+
+///Uniformly random points inside the polygon.
+pub fn polygon_iter<T: Coord>(
+    polygon: &Polygon,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    polygon_iter_from_rng(polygon, rand::thread_rng())
+}
+
+///Same as [`polygon_iter`] but reproducible from a seed.
+pub fn polygon_iter_seeded<T: Coord>(
+    polygon: &Polygon,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    polygon_iter_from_rng(polygon, seeded_rng(seed))
+}
+
+///Same as [`polygon_iter`] but draws from the provided rng.
+pub fn polygon_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    polygon: &Polygon,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    let rect = polygon.bounding_rect().cast_coord();
+    let polygon = Arc::new(polygon.clone());
+    DistIter::new(core::iter::repeat_with(move || {
+        point_from_f64(polygon.sample(&mut rng))
+    }))
+    .with_rect(rect)
+}
+
+///The points of a square lattice with the given spacing that lie inside the polygon.
+///The lattice starts at the start corner of the polygon's bounding rect.
+pub fn polygon_grid_iter<T: Coord>(
+    polygon: &Polygon,
+    spacing: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    assert!(spacing > 0.0, "spacing must be positive");
+
+    let bounds = polygon.bounding_rect();
+    let dim = [
+        ((bounds.x.end - bounds.x.start) / spacing).floor() as usize + 1,
+        ((bounds.y.end - bounds.y.start) / spacing).floor() as usize + 1,
+    ];
+
+    let polygon = Arc::new(polygon.clone());
+    let it = crate::grid_iter(dim)
+        .map(move |[x, y]| {
+            [
+                bounds.x.start + x as f64 * spacing,
+                bounds.y.start + y as f64 * spacing,
+            ]
+        })
+        .filter(move |&p| polygon.contains_point(p))
+        .map(point_from_f64);
+    DistIter::new(it.fuse()).with_rect(bounds.cast_coord())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(start: [f64; 2], size: f64) -> Vec<Point> {
+        let [x, y] = start;
+        vec![[x, y], [x + size, y], [x + size, y + size], [x, y + size]]
+    }
+
+    fn l_shape() -> Polygon {
+        Polygon::new(vec![
+            [0.0, 0.0],
+            [5.0, 0.0],
+            [5.0, 3.0],
+            [2.0, 3.0],
+            [2.0, 5.0],
+            [0.0, 5.0],
+        ])
+        .unwrap()
+    }
+
+    fn two_holes() -> Polygon {
+        Polygon::with_holes(
+            square([0.0, 0.0], 10.0),
+            vec![square([2.0, 2.0], 2.0), square([6.0, 5.0], 2.0)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn non_convex_area() {
+        assert!((l_shape().area() - 19.0).abs() < 1e-9);
+
+        //The winding order should not matter.
+        let mut ring = square([1.0, 1.0], 3.0);
+        ring.reverse();
+        assert!((Polygon::new(ring).unwrap().area() - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn holes_area() {
+        assert!((two_holes().area() - 92.0).abs() < 1e-9);
+
+        //A square with a slot cut down from the top. The pillar at x=9
+        //lines up with the wall of the slot.
+        let slot = vec![
+            [0.0, 0.0],
+            [20.0, 0.0],
+            [20.0, 20.0],
+            [11.0, 20.0],
+            [11.0, 11.0],
+            [10.0, 11.0],
+            [10.0, 20.0],
+            [0.0, 20.0],
+        ];
+        for x in [9.0, 5.0, 15.0, 2.0, 10.0] {
+            let polygon = Polygon::with_holes(slot.clone(), vec![square([x, 5.0], 1.0)]).unwrap();
+            assert!((polygon.area() - 390.0).abs() < 1e-9);
+
+            let points: Vec<[f64; 2]> = polygon_iter_seeded(&polygon, 3).take(2000).collect();
+            assert!(points.iter().all(|&p| polygon.contains_point(p)));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let bow_tie = vec![[0.0, 0.0], [2.0, 2.0], [2.0, 0.0], [0.0, 2.0]];
+        assert_eq!(
+            Polygon::new(bow_tie).err(),
+            Some(PolygonError::TriangulationFailed)
+        );
+
+        //A hole that pokes out of the outer ring.
+        assert_eq!(
+            Polygon::with_holes(square([0.0, 0.0], 10.0), vec![square([8.0, 8.0], 4.0)]).err(),
+            Some(PolygonError::TriangulationFailed)
+        );
+
+        assert_eq!(
+            Polygon::new(vec![[0.0, 0.0], [1.0, 0.0]]).err(),
+            Some(PolygonError::TooFewVertices)
+        );
+        assert_eq!(
+            Polygon::with_holes(square([0.0, 0.0], 10.0), vec![vec![[1.0, 1.0], [2.0, 2.0]]]).err(),
+            Some(PolygonError::TooFewVertices)
+        );
+        assert_eq!(
+            Polygon::new(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]).err(),
+            Some(PolygonError::ZeroArea)
+        );
+    }
+
+    #[test]
+    fn samples_are_inside() {
+        for polygon in [l_shape(), two_holes()] {
+            let points: Vec<[f64; 2]> = polygon_iter_seeded(&polygon, 5).take(5000).collect();
+            assert!(points.iter().all(|&p| polygon.contains_point(p)));
+        }
+
+        let polygon = two_holes();
+        assert!(!polygon.contains_point([3.0, 3.0]));
+        assert!(!polygon.contains_point([7.0, 6.0]));
+        assert!(polygon.contains_point([5.0, 5.0]));
+    }
+}
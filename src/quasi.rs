@@ -0,0 +1,211 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+use rand::RngCore;
+
+use crate::coord::*;
+use crate::seeded_rng;
+use crate::Dist;
+use crate::DistIter;
+
+///Map a point in the unit square into the rect.
+fn unit_to_rect<T: Coord>(rect: &Rect<T>, [x, y]: [f64; 2]) -> [T; 2] {
+    [
+        T::lerp(rect.x.start, rect.x.end, x),
+        T::lerp(rect.y.start, rect.y.end, y),
+    ]
+}
+
+///The van der corput radical inverse of `i` in the given base.
+fn radical_inverse(mut i: u64, base: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut f = inv_base;
+    let mut ans = 0.0;
+    while i > 0 {
+        ans += (i % base) as f64 * f;
+        i /= base;
+        f *= inv_base;
+    }
+    ans
+}
+
+///The halton sequence over the rect, using the given base for each axis.
+///The bases should be coprime, for example `[2,3]`.
+///The first point is the start corner of the rect.
+pub fn halton_iter<T: Coord>(
+    rect: Rect<T>,
+    bases: [u32; 2],
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    assert!(bases[0] >= 2 && bases[1] >= 2, "bases must be at least two");
+    let [bx, by] = [bases[0] as u64, bases[1] as u64];
+    DistIter::new(
+        (0u64..)
+            .map(move |i| unit_to_rect(&rect, [radical_inverse(i, bx), radical_inverse(i, by)])),
+    )
+    .with_rect(rect)
+}
+
+///Direction numbers for the second dimension of the sobol sequence,
+///which uses the primitive polynomial `x+1`.
+const SOBOL_DIRECTIONS: [u32; 32] = {
+    let mut v = [0u32; 32];
+    let mut m = 1u32;
+    let mut k = 0;
+    while k < 32 {
+        v[k] = m << (31 - k);
+        //m_k = 2*m_(k-1) xor m_(k-1)
+        m = (m << 1) ^ m;
+        k += 1;
+    }
+    v
+};
+
+fn sobol(i: u32) -> [u32; 2] {
+    let x = i.reverse_bits();
+
+    let mut y = 0;
+    let mut i = i;
+    let mut k = 0;
+    while i != 0 {
+        if i & 1 == 1 {
+            y ^= SOBOL_DIRECTIONS[k];
+        }
+        i >>= 1;
+        k += 1;
+    }
+    [x, y]
+}
+
+///Owen scrambling using the hash based permutation from
+///Burley, "Practical Hash-based Owen Scrambling", 2020.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    let mut x = x.reverse_bits();
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x.reverse_bits()
+}
+
+fn u32_to_unit(a: u32) -> f64 {
+    a as f64 * (1.0 / (1u64 << 32) as f64)
+}
+
+fn sobol_scrambled<T: Coord>(
+    rect: Rect<T>,
+    seeds: Option<[u32; 2]>,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    DistIter::new((0..1u64 << 32).map(move |i| {
+        let [mut x, mut y] = sobol(i as u32);
+        if let Some([sx, sy]) = seeds {
+            x = nested_uniform_scramble(x, sx);
+            y = nested_uniform_scramble(y, sy);
+        }
+        unit_to_rect(&rect, [u32_to_unit(x), u32_to_unit(y)])
+    }))
+    .with_rect(rect)
+}
+
+///The two dimensional sobol sequence over the rect.
+///Ends after `2^32` points.
+///The first point is the start corner of the rect.
+pub fn sobol_iter<T: Coord>(
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    sobol_scrambled(rect, None)
+}
+
+///Same as [`sobol_iter`] but with owen scrambling, which keeps the even coverage
+///of the sequence while removing its regular structure.
+///The same seed always gives the same scrambling.
+pub fn sobol_iter_owen_seeded<T: Coord>(
+    rect: Rect<T>,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    sobol_iter_owen_from_rng(rect, seeded_rng(seed))
+}
+
+///Same as [`sobol_iter_owen_seeded`] but draws the scrambling from the provided rng.
+pub fn sobol_iter_owen_from_rng<T: Coord, R: RngCore>(
+    rect: Rect<T>,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    let seeds = [rng.next_u32(), rng.next_u32()];
+    sobol_scrambled(rect, Some(seeds))
+}
+
+///The fractional part of `a`.
+fn frac(a: f64) -> f64 {
+    a - a.floor()
+}
+
+///The plastic number, the unique real solution to `x^3=x+1`.
+const PLASTIC: f64 = 1.324_717_957_244_746;
+
+pub(crate) fn r2(i: usize) -> [f64; 2] {
+    let a1 = 1.0 / PLASTIC;
+    let a2 = 1.0 / (PLASTIC * PLASTIC);
+    [frac(0.5 + a1 * i as f64), frac(0.5 + a2 * i as f64)]
+}
+
+///The R2 additive recurrence sequence by Martin Roberts over the rect.
+///Every prefix of the sequence is evenly spread out.
+pub fn r2_iter<T: Coord>(
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    DistIter::new((0..).map(move |i| unit_to_rect(&rect, r2(i)))).with_rect(rect)
+}
+
+///The first `num_bots` points of [`r2_iter`].
+pub fn r2_rect_iter<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + FusedIterator
+       + Clone
+       + Send
+       + Sync {
+    DistIter::new((0..num_bots).map(move |i| unit_to_rect(&rect, r2(i))))
+        .with_len(num_bots)
+        .with_rect(rect)
+}
+
+const INV_PHI: f64 = 0.618_033_988_749_895;
+
+///A fibonacci lattice of exactly `num_bots` points that fills the rect.
+///The points are evenly spaced along the x axis and
+///advance by the golden ratio along the y axis.
+pub fn fib_lattice_rect_iter<T: Coord>(
+    num_bots: usize,
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T>
+       + ExactSizeIterator
+       + DoubleEndedIterator
+       + FusedIterator
+       + Clone
+       + Send
+       + Sync {
+    let n = num_bots as f64;
+    DistIter::new((0..num_bots).map(move |i| {
+        let i = i as f64;
+        unit_to_rect(&rect, [(i + 0.5) / n, frac(i * INV_PHI)])
+    }))
+    .with_len(num_bots)
+    .with_rect(rect)
+}
+
+///A fibonacci lattice that does not need to know the number of points up front.
+///Since the total is unknown, the x axis uses the base two radical inverse
+///instead of even spacing, so every prefix of the sequence is evenly spread out.
+pub fn fib_lattice_iter<T: Coord>(
+    rect: Rect<T>,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    DistIter::new((0u64..).map(move |i| {
+        let x = radical_inverse(i, 2);
+        let y = frac(i as f64 * INV_PHI);
+        unit_to_rect(&rect, [x, y])
+    }))
+    .with_rect(rect)
+}
@@ -0,0 +1,92 @@
+use axgeom::*;
+
+use crate::coord::*;
+use crate::Dist;
+use crate::DistIter;
+
+///Generates `num` rectangles that all share the `anchor` corner,
+///each one `step` wider than the last.
+///The height of each rectangle is its width divided by `aspect_ratio`.
+///Every rectangle overlaps every other rectangle.
+///A negative `step` grows the rectangles towards negative infinity.
+pub fn nested_iter<T: Coord>(
+    anchor: [T; 2],
+    num: usize,
+    step: T,
+    aspect_ratio: f64,
+) -> impl Dist<Item = Rect<T>, Num = T> + ExactSizeIterator + DoubleEndedIterator + Clone + Send + Sync
+{
+    assert!(aspect_ratio > 0.0, "aspect ratio must be positive");
+
+    let anchor = point_to_f64(anchor);
+    let step = step.to_f64();
+
+    let doll = move |i: usize| {
+        let width = step * (i + 1) as f64;
+        let height = width / aspect_ratio;
+
+        let x = [anchor[0], anchor[0] + width];
+        let y = [anchor[1], anchor[1] + height];
+        Rect::new(
+            T::from_f64(x[0].min(x[1])),
+            T::from_f64(x[0].max(x[1])),
+            T::from_f64(y[0].min(y[1])),
+            T::from_f64(y[0].max(y[1])),
+        )
+    };
+
+    //The last rect contains all the others.
+    let it = DistIter::new((0..num).map(doll)).with_len(num);
+    match num.checked_sub(1) {
+        Some(last) => it.with_rect(doll(last)),
+        None => it,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::intersecting_pairs;
+
+    #[test]
+    fn every_pair_intersects() {
+        let n = 12;
+        let rects: Vec<Rect<i32>> = nested_iter([5, -3], n, 4, 1.5).collect();
+        assert_eq!(intersecting_pairs(&rects).len(), n * (n - 1) / 2);
+
+        let rects: Vec<Rect<f32>> = nested_iter([5.0, -3.0], n, -0.5, 0.25).collect();
+        assert_eq!(intersecting_pairs(&rects).len(), n * (n - 1) / 2);
+    }
+
+    #[test]
+    fn shares_anchor_corner() {
+        let it = nested_iter([5.0, -3.0], 10, 2.0, 2.0);
+        let rects: Vec<Rect<f64>> = it.clone().collect();
+        assert_eq!(it.bounding_rect(), Some(rects[9]));
+        for (i, r) in rects.iter().enumerate() {
+            let width = 2.0 * (i + 1) as f64;
+            assert_eq!(*r, Rect::new(5.0, 5.0 + width, -3.0, -3.0 + width / 2.0));
+        }
+    }
+
+    #[test]
+    fn negative_step_grows_towards_negative() {
+        let it = nested_iter([5, -3], 10, -2, 1.0);
+        let rects: Vec<Rect<i32>> = it.clone().collect();
+        assert_eq!(it.bounding_rect(), Some(rects[9]));
+        for (i, r) in rects.iter().enumerate() {
+            let width = 2 * (i as i32 + 1);
+            assert_eq!(*r, Rect::new(5 - width, 5, -3 - width, -3));
+        }
+
+        let rev: Vec<Rect<i32>> = it.rev().collect();
+        assert_eq!(rev[0], rects[9]);
+    }
+
+    #[test]
+    fn no_rects() {
+        let it = nested_iter([0, 0], 0, 1, 1.0);
+        assert_eq!(it.len(), 0);
+        assert_eq!(it.bounding_rect(), None);
+    }
+}
@@ -1,4 +1,14 @@
 use axgeom::*;
+use core::f64::consts::TAU;
+use core::iter::FusedIterator;
+use rand::RngCore;
+
+use crate::coord::CastCoord;
+use crate::coord::*;
+use crate::gaussian::standard_normal;
+use crate::seeded_rng;
+use crate::Dist;
+use crate::DistIter;
 
 #[derive(Clone)]
 pub struct Spiral {
@@ -9,23 +19,46 @@ pub struct Spiral {
     width: f32,
 }
 
+///A wrapper around a Spiral that produces integers.
+///
+///The points are rounded down like every other [`Coord`] conversion.
+///Before 0.5 they were truncated towards zero, so points with negative
+///coordinates are now one further from the origin.
 pub struct SpiralInt(Spiral);
 impl Iterator for SpiralInt {
     type Item = Vec2<i32>;
     fn next(&mut self) -> Option<Vec2<i32>> {
-        self.0.next().map(|a| a.inner_as())
+        self.0.next().map(CastCoord::<i32>::cast_coord)
     }
 }
 impl std::iter::FusedIterator for SpiralInt {}
+impl Dist for SpiralInt {
+    type Num = i32;
+    fn exact_len(&self) -> Option<usize> {
+        None
+    }
+    fn bounding_rect(&self) -> Option<Rect<i32>> {
+        None
+    }
+}
 
 pub struct SpiralF64(Spiral);
 impl Iterator for SpiralF64 {
     type Item = Vec2<f64>;
     fn next(&mut self) -> Option<Vec2<f64>> {
-        self.0.next().map(|a| vec2(a.x as f64, a.y as f64))
+        self.0.next().map(CastCoord::<f64>::cast_coord)
     }
 }
 impl std::iter::FusedIterator for SpiralF64 {}
+impl Dist for SpiralF64 {
+    type Num = f64;
+    fn exact_len(&self) -> Option<usize> {
+        None
+    }
+    fn bounding_rect(&self) -> Option<Rect<f64>> {
+        None
+    }
+}
 
 impl Spiral {
     #[deprecated(since = "0.3.1", note = "use spiral_iter() instead")]
@@ -53,6 +86,15 @@ impl Spiral {
 }
 
 impl std::iter::FusedIterator for Spiral {}
+impl Dist for Spiral {
+    type Num = f32;
+    fn exact_len(&self) -> Option<usize> {
+        None
+    }
+    fn bounding_rect(&self) -> Option<Rect<f32>> {
+        None
+    }
+}
 
 impl Iterator for Spiral {
     type Item = Vec2<f32>;
@@ -67,3 +109,438 @@ impl Iterator for Spiral {
         Some(vec2(x, y))
     }
 }
+
+///The shape of a spiral, expressed in polar coordinates about its center.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpiralShape {
+    ///`r = gap*theta/TAU`, so neighbouring turns are `gap` apart.
+    Archimedean { gap: f64 },
+    ///`r = start_radius*e^(growth*theta)`.
+    ///The growth must not be negative. A growth of zero gives a circle.
+    Logarithmic { start_radius: f64, growth: f64 },
+    ///`r = scale*sqrt(theta)`, so each turn encloses the same area.
+    Fermat { scale: f64 },
+}
+
+impl SpiralShape {
+    fn assert_valid(&self) {
+        match *self {
+            SpiralShape::Archimedean { gap } => assert!(gap > 0.0, "gap must be positive"),
+            SpiralShape::Logarithmic {
+                start_radius,
+                growth,
+            } => {
+                assert!(start_radius > 0.0, "start radius must be positive");
+                assert!(
+                    growth >= 0.0 && growth.is_finite(),
+                    "growth must not be negative"
+                );
+            }
+            SpiralShape::Fermat { scale } => assert!(scale > 0.0, "scale must be positive"),
+        }
+    }
+
+    ///The radius and angle at parameter `u`.
+    ///Fermat spirals are parameterized by `sqrt(theta)` so that their speed is finite at the center.
+    fn polar(&self, u: f64) -> (f64, f64) {
+        match *self {
+            SpiralShape::Archimedean { gap } => (gap * u / TAU, u),
+            SpiralShape::Logarithmic {
+                start_radius,
+                growth,
+            } => (start_radius * (growth * u).exp(), u),
+            SpiralShape::Fermat { scale } => (scale * u, u * u),
+        }
+    }
+
+    ///The length of the derivative of the position with respect to `u`.
+    fn speed(&self, u: f64) -> f64 {
+        match *self {
+            SpiralShape::Archimedean { gap } => gap / TAU * (1.0 + u * u).sqrt(),
+            SpiralShape::Logarithmic { growth, .. } => {
+                self.polar(u).0 * (1.0 + growth * growth).sqrt()
+            }
+            SpiralShape::Fermat { scale } => scale * (1.0 + 4.0 * u.powi(4)).sqrt(),
+        }
+    }
+
+    ///The length of the spiral between parameters `u0` and `u1`.
+    fn length(&self, u0: f64, u1: f64) -> f64 {
+        match *self {
+            SpiralShape::Logarithmic { growth, .. } if growth > 0.0 => {
+                let k = growth;
+                (1.0 + k * k).sqrt() / k * (self.polar(u1).0 - self.polar(u0).0)
+            }
+            SpiralShape::Logarithmic { start_radius, .. } => start_radius * (u1 - u0),
+            SpiralShape::Archimedean { gap } => {
+                //The length from the center is b/2*(theta*sqrt(1+theta^2)+asinh(theta)).
+                let f = |t: f64| t * (1.0 + t * t).sqrt() + t.asinh();
+                gap / TAU / 2.0 * (f(u1) - f(u0))
+            }
+            SpiralShape::Fermat { .. } => {
+                //Split the integral so that each piece covers at most a sixteenth of a turn,
+                //otherwise long steps around a small spiral are inaccurate.
+                let turns = (u1 * u1 - u0 * u0).abs() / TAU;
+                let pieces = ((turns * 16.0).ceil() as usize).max(1);
+                let width = (u1 - u0) / pieces as f64;
+                (0..pieces)
+                    .map(|i| {
+                        let a = u0 + width * i as f64;
+                        gauss_legendre(|u| self.speed(u), a, a + width)
+                    })
+                    .sum()
+            }
+        }
+    }
+
+    ///Find the parameter that lies `spacing` further along the spiral than `u`.
+    fn step(&self, u: f64, spacing: f64) -> f64 {
+        //Newton's method, starting from a linear guess.
+        let mut next = u + spacing / self.speed(u);
+        for _ in 0..16 {
+            let err = self.length(u, next) - spacing;
+            let delta = err / self.speed(next);
+            next -= delta;
+            //Far from the center the lengths are large enough that rounding
+            //errors stop the error from shrinking, so also stop once the steps are tiny.
+            if err.abs() <= spacing * 1e-12 || delta.abs() <= next.abs() * 1e-15 {
+                break;
+            }
+        }
+        next
+    }
+}
+
+///Integrate `func` between `a` and `b` using five point Gauss-Legendre quadrature.
+fn gauss_legendre(func: impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    const NODES: [(f64, f64); 5] = [
+        (0.0, 0.568_888_888_888_888_9),
+        (-0.538_469_310_105_683, 0.478_628_670_499_366_5),
+        (0.538_469_310_105_683, 0.478_628_670_499_366_5),
+        (-0.906_179_845_938_664, 0.236_926_885_056_189_08),
+        (0.906_179_845_938_664, 0.236_926_885_056_189_08),
+    ];
+    let half = (b - a) / 2.0;
+    let mid = (a + b) / 2.0;
+    NODES
+        .iter()
+        .map(|&(x, w)| w * func(mid + half * x))
+        .sum::<f64>()
+        * half
+}
+
+///The radius and angle of points spaced `spacing` apart along the spiral.
+#[derive(Clone)]
+struct ArcSteps {
+    shape: SpiralShape,
+    spacing: f64,
+    u: f64,
+    rotation: f64,
+}
+
+impl Iterator for ArcSteps {
+    type Item = (f64, f64);
+    fn next(&mut self) -> Option<(f64, f64)> {
+        let (r, angle) = self.shape.polar(self.u);
+        self.u = self.shape.step(self.u, self.spacing);
+        Some((r, angle + self.rotation))
+    }
+}
+
+impl FusedIterator for ArcSteps {}
+
+fn arc_steps(shape: SpiralShape, spacing: f64) -> ArcSteps {
+    shape.assert_valid();
+    assert!(spacing > 0.0, "spacing must be positive");
+    ArcSteps {
+        shape,
+        spacing,
+        u: 0.0,
+        rotation: 0.0,
+    }
+}
+
+fn polar_to_point<T: Coord>(center: [f64; 2], (r, angle): (f64, f64)) -> [T; 2] {
+    let (sin, cos) = angle.sin_cos();
+    point_from_f64([center[0] + cos * r, center[1] + sin * r])
+}
+
+///Points spaced exactly `spacing` apart along any spiral shape.
+pub fn shape_spiral_iter<T: Coord>(
+    center: [T; 2],
+    shape: SpiralShape,
+    spacing: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    let center = point_to_f64(center);
+    DistIter::new(arc_steps(shape, spacing).map(move |p| polar_to_point(center, p)))
+}
+
+///An archimedean spiral with neighbouring turns `gap` apart and points spaced exactly
+///`spacing` apart along it, found by inverting the closed form of its arc length.
+///The first point is `start_radius` from the center at `start_angle` radians,
+///and the spiral winds outward counter clockwise from there.
+pub fn archimedean_iter<T: Coord>(
+    center: [T; 2],
+    gap: f64,
+    spacing: f64,
+    start_radius: f64,
+    start_angle: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    assert!(start_radius >= 0.0, "start radius must not be negative");
+    let center = point_to_f64(center);
+
+    let mut steps = arc_steps(SpiralShape::Archimedean { gap }, spacing);
+    steps.u = start_radius * TAU / gap;
+    steps.rotation = start_angle - steps.u;
+    DistIter::new(steps.map(move |p| polar_to_point(center, p)))
+}
+
+///A logarithmic spiral, `r = start_radius*e^(growth*theta)`, with points spaced exactly `spacing` apart along it.
+pub fn log_spiral_iter<T: Coord>(
+    center: [T; 2],
+    start_radius: f64,
+    growth: f64,
+    spacing: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    shape_spiral_iter(
+        center,
+        SpiralShape::Logarithmic {
+            start_radius,
+            growth,
+        },
+        spacing,
+    )
+}
+
+///A fermat spiral, `r = scale*sqrt(theta)`, with points spaced exactly `spacing` apart along it.
+///Use [`multi_arm_iter`] with two arms for the full double spiral.
+pub fn fermat_spiral_iter<T: Coord>(
+    center: [T; 2],
+    scale: f64,
+    spacing: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    shape_spiral_iter(center, SpiralShape::Fermat { scale }, spacing)
+}
+
+///A spiral with `arms` copies of the shape rotated evenly about the center,
+///such as a galaxy. Points are spaced exactly `spacing` apart along each arm
+///and then moved by a gaussian with a standard deviation of `scatter`.
+///The arms take turns yielding points, so every prefix is balanced between them.
+pub fn multi_arm_iter<T: Coord>(
+    center: [T; 2],
+    arms: usize,
+    shape: SpiralShape,
+    spacing: f64,
+    scatter: f64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    multi_arm_iter_from_rng(center, arms, shape, spacing, scatter, rand::thread_rng())
+}
+
+///Same as [`multi_arm_iter`] but reproducible from a seed.
+pub fn multi_arm_iter_seeded<T: Coord>(
+    center: [T; 2],
+    arms: usize,
+    shape: SpiralShape,
+    spacing: f64,
+    scatter: f64,
+    seed: u64,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone + Send + Sync {
+    multi_arm_iter_from_rng(center, arms, shape, spacing, scatter, seeded_rng(seed))
+}
+
+///Same as [`multi_arm_iter`] but draws from the provided rng.
+pub fn multi_arm_iter_from_rng<T: Coord, R: RngCore + Clone>(
+    center: [T; 2],
+    arms: usize,
+    shape: SpiralShape,
+    spacing: f64,
+    scatter: f64,
+    mut rng: R,
+) -> impl Dist<Item = [T; 2], Num = T> + FusedIterator + Clone {
+    let center = point_to_f64(center);
+    let arms = arms.max(1);
+
+    let it = arc_steps(shape, spacing)
+        .flat_map(move |(r, angle)| {
+            (0..arms).map(move |k| (r, angle + TAU * k as f64 / arms as f64))
+        })
+        .map(move |(r, angle)| {
+            let (sin, cos) = angle.sin_cos();
+            let [dx, dy] = if scatter > 0.0 {
+                standard_normal(&mut rng)
+            } else {
+                [0.0, 0.0]
+            };
+            point_from_f64([
+                center[0] + cos * r + dx * scatter,
+                center[1] + sin * r + dy * scatter,
+            ])
+        });
+    DistIter::new(it)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::Affine;
+
+    fn shapes() -> [SpiralShape; 5] {
+        [
+            SpiralShape::Archimedean { gap: 3.0 },
+            SpiralShape::Logarithmic {
+                start_radius: 2.0,
+                growth: 0.2,
+            },
+            SpiralShape::Logarithmic {
+                start_radius: 2.0,
+                growth: 0.0,
+            },
+            SpiralShape::Fermat { scale: 10.0 },
+            SpiralShape::Fermat { scale: 0.1 },
+        ]
+    }
+
+    ///The length of the spiral between two parameters, measured along a fine polyline.
+    fn polyline_length(shape: SpiralShape, u0: f64, u1: f64) -> f64 {
+        let point = |u: f64| {
+            let (r, angle) = shape.polar(u);
+            [r * angle.cos(), r * angle.sin()]
+        };
+        let n = 1000;
+        (0..n)
+            .map(|i| {
+                let a = point(u0 + (u1 - u0) * i as f64 / n as f64);
+                let b = point(u0 + (u1 - u0) * (i + 1) as f64 / n as f64);
+                ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn constant_arc_length() {
+        for shape in shapes() {
+            for spacing in [0.05, 0.5, 4.0] {
+                let mut u = 0.0;
+                for i in 0..2000 {
+                    let next = shape.step(u, spacing);
+                    assert!(next > u);
+                    let len = shape.length(u, next);
+                    assert!(
+                        (len - spacing).abs() <= spacing * 1e-9,
+                        "{:?} {} {}",
+                        shape,
+                        spacing,
+                        len
+                    );
+                    if i % 100 == 0 {
+                        let len = polyline_length(shape, u, next);
+                        assert!((len - spacing).abs() <= spacing * 1e-4, "{:?}", shape);
+                    }
+                    u = next;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn multi_arm_rotates_arms() {
+        let shape = SpiralShape::Logarithmic {
+            start_radius: 1.0,
+            growth: 0.3,
+        };
+        let single: Vec<[f64; 2]> = shape_spiral_iter([0.0, 0.0], shape, 1.0).take(4).collect();
+        let multi: Vec<[f64; 2]> = multi_arm_iter_seeded([0.0, 0.0], 3, shape, 1.0, 0.0, 1)
+            .take(12)
+            .collect();
+        for (i, p) in multi.iter().enumerate() {
+            let s = single[i / 3];
+            let [x, y] = Affine::rotate(TAU * (i % 3) as f64 / 3.0).apply(s);
+            assert!((p[0] - x).abs() < 1e-9 && (p[1] - y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn negative_growth() {
+        let shape = SpiralShape::Logarithmic {
+            start_radius: 1.0,
+            growth: -0.1,
+        };
+        let _ = shape_spiral_iter([0.0, 0.0], shape, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_start_radius() {
+        let _ = log_spiral_iter([0.0, 0.0], 0.0, 0.1, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_gap() {
+        let _ = shape_spiral_iter([0.0, 0.0], SpiralShape::Archimedean { gap: 0.0 }, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_scale() {
+        let _ = fermat_spiral_iter([0.0, 0.0], 0.0, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_spacing() {
+        let _ = multi_arm_iter_seeded(
+            [0.0, 0.0],
+            2,
+            SpiralShape::Fermat { scale: 1.0 },
+            0.0,
+            0.0,
+            1,
+        );
+    }
+
+    #[test]
+    fn archimedean_start_and_spacing() {
+        let center = [5.0, -3.0];
+        for (gap, spacing, start_radius, start_angle) in [
+            (3.0, 0.7, 10.0, 1.0),
+            (1.0, 0.1, 0.0, 0.0),
+            (0.5, 2.0, 2.5, -2.0),
+        ] {
+            let points: Vec<[f64; 2]> =
+                archimedean_iter(center, gap, spacing, start_radius, start_angle)
+                    .take(5000)
+                    .collect();
+
+            let [dx, dy] = [points[0][0] - center[0], points[0][1] - center[1]];
+            assert!(((dx * dx + dy * dy).sqrt() - start_radius).abs() < 1e-9);
+            if start_radius > 0.0 {
+                assert!((dy.atan2(dx) - start_angle).abs() < 1e-9);
+            }
+
+            //Recover the angle of each point from its radius, r=b*theta,
+            //and measure the arc length with the closed form.
+            let b = gap / TAU;
+            let theta =
+                |p: &[f64; 2]| ((p[0] - center[0]).powi(2) + (p[1] - center[1]).powi(2)).sqrt() / b;
+            let length = |t: f64| b / 2.0 * (t * (1.0 + t * t).sqrt() + t.asinh());
+            for w in points.windows(2) {
+                let len = length(theta(&w[1])) - length(theta(&w[0]));
+                assert!((len - spacing).abs() < 1e-6, "{} {}", len, spacing);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn archimedean_zero_gap() {
+        let _ = archimedean_iter([0.0, 0.0], 0.0, 1.0, 5.0, 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn archimedean_zero_spacing() {
+        let _ = archimedean_iter([0.0, 0.0], 1.0, 0.0, 5.0, 0.0);
+    }
+}
@@ -0,0 +1,155 @@
+use axgeom::*;
+
+///Returns every pair of AABBs that intersect, found by brute force.
+///
+///Each pair is ordered so that the smaller index comes first,
+///and the pairs are sorted. AABBs that only touch count as intersecting.
+pub fn intersecting_pairs<T: PartialOrd + Copy>(aabbs: &[Rect<T>]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, a) in aabbs.iter().enumerate() {
+        for (j, b) in aabbs.iter().enumerate().skip(i + 1) {
+            if a.intersects_rect(b) {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+///Put a list of pairs into the same canonical form as [`intersecting_pairs`].
+pub fn canonical_pairs(pairs: impl IntoIterator<Item = (usize, usize)>) -> Vec<(usize, usize)> {
+    let mut pairs: Vec<_> = pairs
+        .into_iter()
+        .map(|(a, b)| if a <= b { (a, b) } else { (b, a) })
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+///The difference between the expected and a candidate set of intersecting pairs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PairDiff {
+    ///Pairs that intersect but were not in the candidate result.
+    pub missing: Vec<(usize, usize)>,
+    ///Pairs in the candidate result that do not intersect.
+    pub extra: Vec<(usize, usize)>,
+}
+
+impl PairDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+///Compare the expected pairs against a candidate result.
+///The candidate pairs may be in any order and each pair may be in either order.
+///
+///The expected pairs must be in canonical form.
+pub fn diff_pairs(
+    expected: &[(usize, usize)],
+    candidate: impl IntoIterator<Item = (usize, usize)>,
+) -> PairDiff {
+    let candidate = canonical_pairs(candidate);
+
+    let mut diff = PairDiff::default();
+    let mut a = expected.iter().peekable();
+    let mut b = candidate.iter().peekable();
+    loop {
+        match (a.peek(), b.peek()) {
+            (Some(&&x), Some(&&y)) => {
+                if x < y {
+                    diff.missing.push(x);
+                    a.next();
+                } else if y < x {
+                    diff.extra.push(y);
+                    b.next();
+                } else {
+                    a.next();
+                    b.next();
+                }
+            }
+            (Some(&&x), None) => {
+                diff.missing.push(x);
+                a.next();
+            }
+            (None, Some(&&y)) => {
+                diff.extra.push(y);
+                b.next();
+            }
+            (None, None) => break,
+        }
+    }
+    diff
+}
+
+///Check a candidate result, such as one produced by a tree based collision system,
+///against the brute force result.
+pub fn check_pairs<T: PartialOrd + Copy>(
+    aabbs: &[Rect<T>],
+    candidate: impl IntoIterator<Item = (usize, usize)>,
+) -> PairDiff {
+    diff_pairs(&intersecting_pairs(aabbs), candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aabbs() -> Vec<Rect<i32>> {
+        vec![
+            rect(0, 10, 0, 10),
+            rect(5, 15, 5, 15),
+            //Only touches the first one along its right edge.
+            rect(10, 20, 0, 4),
+            rect(100, 110, 100, 110),
+        ]
+    }
+
+    #[test]
+    fn touching_aabbs_intersect() {
+        assert_eq!(intersecting_pairs(&aabbs()), [(0, 1), (0, 2)]);
+        assert_eq!(
+            intersecting_pairs(&[rect(0.0, 1.0, 0.0, 1.0), rect(1.0, 2.0, 1.0, 2.0)]),
+            [(0, 1)]
+        );
+    }
+
+    #[test]
+    fn matching_candidate() {
+        assert!(check_pairs(&aabbs(), [(0, 1), (0, 2)]).is_empty());
+    }
+
+    #[test]
+    fn reversed_and_duplicated_candidate() {
+        let diff = check_pairs(&aabbs(), [(2, 0), (1, 0), (0, 2), (0, 1)]);
+        assert!(diff.is_empty(), "{:?}", diff);
+    }
+
+    #[test]
+    fn missing_pairs() {
+        let diff = check_pairs(&aabbs(), [(1, 0)]);
+        assert_eq!(diff.missing, [(0, 2)]);
+        assert!(diff.extra.is_empty());
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn extra_pairs() {
+        let diff = check_pairs(&aabbs(), [(0, 1), (0, 2), (3, 0), (2, 3)]);
+        assert!(diff.missing.is_empty());
+        assert_eq!(diff.extra, [(0, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn missing_and_extra_pairs() {
+        let diff = diff_pairs(&[(0, 1), (2, 3)], [(3, 2), (1, 4)]);
+        assert_eq!(
+            diff,
+            PairDiff {
+                missing: vec![(0, 1)],
+                extra: vec![(1, 4)],
+            }
+        );
+    }
+}
@@ -0,0 +1,585 @@
+use axgeom::*;
+use core::iter::FusedIterator;
+
+use crate::aabb::Point2;
+use crate::coord::*;
+use crate::Dist;
+
+///A 2d affine transform, mapping `p` to `matrix * p + offset`.
+///`matrix` is stored as rows.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Affine {
+    pub matrix: [[f64; 2]; 2],
+    pub offset: [f64; 2],
+}
+
+impl Default for Affine {
+    fn default() -> Affine {
+        Affine::identity()
+    }
+}
+
+impl Affine {
+    pub fn new(matrix: [[f64; 2]; 2], offset: [f64; 2]) -> Affine {
+        Affine { matrix, offset }
+    }
+
+    ///Leaves every point where it is.
+    pub fn identity() -> Affine {
+        Affine::new([[1.0, 0.0], [0.0, 1.0]], [0.0, 0.0])
+    }
+
+    pub fn translate(offset: [f64; 2]) -> Affine {
+        Affine::new([[1.0, 0.0], [0.0, 1.0]], offset)
+    }
+
+    ///Rotate counter clockwise by `angle` radians about the origin.
+    pub fn rotate(angle: f64) -> Affine {
+        let (s, c) = angle.sin_cos();
+        Affine::new([[c, -s], [s, c]], [0.0, 0.0])
+    }
+
+    ///Rotate counter clockwise by `angle` radians about `center`.
+    pub fn rotate_about(center: [f64; 2], angle: f64) -> Affine {
+        Affine::rotate(angle).about(center)
+    }
+
+    ///Scale each axis independently about the origin.
+    pub fn scale(scale: [f64; 2]) -> Affine {
+        Affine::new([[scale[0], 0.0], [0.0, scale[1]]], [0.0, 0.0])
+    }
+
+    ///Scale each axis independently about `center`.
+    pub fn scale_about(center: [f64; 2], scale: [f64; 2]) -> Affine {
+        Affine::scale(scale).about(center)
+    }
+
+    ///Shear about the origin, mapping `[x,y]` to `[x+shear[0]*y, y+shear[1]*x]`.
+    pub fn shear(shear: [f64; 2]) -> Affine {
+        Affine::new([[1.0, shear[0]], [shear[1], 1.0]], [0.0, 0.0])
+    }
+
+    ///Mirror across the line that passes through `point` at `angle` radians from the x axis.
+    ///An angle of zero mirrors across a horizontal line, flipping the y coordinates.
+    pub fn mirror(point: [f64; 2], angle: f64) -> Affine {
+        let (s, c) = (2.0 * angle).sin_cos();
+        Affine::new([[c, s], [s, -c]], [0.0, 0.0]).about(point)
+    }
+
+    ///The transform that applies `self` and then `other`.
+    pub fn then(self, other: Affine) -> Affine {
+        let [[a, b], [c, d]] = other.matrix;
+        let [[e, f], [g, h]] = self.matrix;
+        Affine::new(
+            [
+                [a * e + b * g, a * f + b * h],
+                [c * e + d * g, c * f + d * h],
+            ],
+            other.apply(self.offset),
+        )
+    }
+
+    ///The transform that undoes `self`, if it is not degenerate.
+    pub fn inverse(&self) -> Option<Affine> {
+        let [[a, b], [c, d]] = self.matrix;
+        let det = a * d - b * c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let matrix = [[d / det, -b / det], [-c / det, a / det]];
+        let linear = Affine::new(matrix, [0.0, 0.0]);
+        let [x, y] = linear.apply(self.offset);
+        Some(Affine::new(matrix, [-x, -y]))
+    }
+
+    pub fn apply(&self, point: [f64; 2]) -> [f64; 2] {
+        let [[a, b], [c, d]] = self.matrix;
+        let [x, y] = point;
+        [
+            a * x + b * y + self.offset[0],
+            c * x + d * y + self.offset[1],
+        ]
+    }
+
+    ///The smallest rect that contains the transformed rect.
+    pub fn apply_rect(&self, rect: Rect<f64>) -> Rect<f64> {
+        let corners = [
+            [rect.x.start, rect.y.start],
+            [rect.x.end, rect.y.start],
+            [rect.x.start, rect.y.end],
+            [rect.x.end, rect.y.end],
+        ]
+        .map(|p| self.apply(p));
+
+        bounding(&corners).unwrap()
+    }
+
+    ///Conjugate the transform so that it acts about `center` instead of the origin.
+    fn about(self, center: [f64; 2]) -> Affine {
+        Affine::translate([-center[0], -center[1]])
+            .then(self)
+            .then(Affine::translate(center))
+    }
+}
+
+///Applies an [`Affine`] transform to every point of a distribution.
+///Created with the methods of [`TransformExt`].
+#[derive(Clone)]
+pub struct Transform<I> {
+    inner: I,
+    affine: Affine,
+}
+
+impl<I> Transform<I> {
+    pub fn affine(&self) -> Affine {
+        self.affine
+    }
+}
+
+fn to_f64<P: Point2>(p: P) -> [f64; 2]
+where
+    P::Num: Coord,
+{
+    let v = p.to_vec2();
+    [v.x.to_f64(), v.y.to_f64()]
+}
+
+fn apply_point<P: Point2>(affine: &Affine, p: P) -> P
+where
+    P::Num: Coord,
+{
+    let [x, y] = affine.apply(to_f64(p));
+    P::from_vec2(vec2(P::Num::from_f64(x), P::Num::from_f64(y)))
+}
+
+impl<I: Iterator> Iterator for Transform<I>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        let affine = self.affine;
+        self.inner.next().map(|p| apply_point(&affine, p))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for Transform<I>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+    fn next_back(&mut self) -> Option<I::Item> {
+        let affine = self.affine;
+        self.inner.next_back().map(|p| apply_point(&affine, p))
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for Transform<I>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+}
+
+impl<I: FusedIterator> FusedIterator for Transform<I>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+}
+
+impl<T: Coord, I: Dist<Num = T>> Dist for Transform<I>
+where
+    I::Item: Point2<Num = T>,
+{
+    type Num = T;
+    fn exact_len(&self) -> Option<usize> {
+        self.inner.exact_len()
+    }
+    fn bounding_rect(&self) -> Option<Rect<T>> {
+        self.inner
+            .bounding_rect()
+            .map(|r| self.affine.apply_rect(rect_to_f64(r)).cast_coord())
+    }
+}
+
+///Extension methods to transform the points of any distribution,
+///whether it yields `[T; 2]` or `Vec2<T>`.
+///
+///Every call converts back to the coordinate type of the distribution, so with
+///integer coordinates prefer building one [`Affine`] with [`Affine::then`]
+///and calling [`TransformExt::transform`] once.
+pub trait TransformExt: Iterator + Sized
+where
+    Self::Item: Point2,
+    <Self::Item as Point2>::Num: Coord,
+{
+    fn transform(self, affine: Affine) -> Transform<Self> {
+        Transform {
+            inner: self,
+            affine,
+        }
+    }
+
+    fn translate(self, offset: [f64; 2]) -> Transform<Self> {
+        self.transform(Affine::translate(offset))
+    }
+
+    ///Rotate counter clockwise by `angle` radians about `center`.
+    fn rotate_about(self, center: [f64; 2], angle: f64) -> Transform<Self> {
+        self.transform(Affine::rotate_about(center, angle))
+    }
+
+    ///Scale each axis independently about `center`.
+    fn scale_about(self, center: [f64; 2], scale: [f64; 2]) -> Transform<Self> {
+        self.transform(Affine::scale_about(center, scale))
+    }
+
+    ///Shear about the origin. See [`Affine::shear`].
+    fn shear(self, shear: [f64; 2]) -> Transform<Self> {
+        self.transform(Affine::shear(shear))
+    }
+
+    ///Mirror across a line. See [`Affine::mirror`].
+    fn mirror(self, point: [f64; 2], angle: f64) -> Transform<Self> {
+        self.transform(Affine::mirror(point, angle))
+    }
+
+    ///Take the first `num` points and map them into the target rect.
+    ///See [`Fit`] for how the points are scaled.
+    ///Yields nothing if the target rect is inverted or has NaN coordinates.
+    fn fit_rect<U: Coord>(self, num: usize, target: Rect<U>, fit: Fit) -> FitRect<U> {
+        FitRect::new(self.take(num).map(to_f64).collect(), target, fit)
+    }
+}
+
+impl<I: Iterator> TransformExt for I
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+}
+
+///How [`TransformExt::fit_rect`] scales the points into the target rect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fit {
+    ///Scale both axes by the same amount so that the points touch
+    ///the target along one axis, and center them along the other.
+    Contain,
+    ///Scale each axis independently so that the points touch every side of the target.
+    Stretch,
+}
+
+///A fixed set of points that were mapped into a target rect.
+///Created with [`TransformExt::fit_rect`].
+#[derive(Clone)]
+pub struct FitRect<T> {
+    points: std::vec::IntoIter<[T; 2]>,
+    affine: Affine,
+    rect: Option<Rect<T>>,
+}
+
+impl<T: Coord> FitRect<T> {
+    fn new(points: Vec<[f64; 2]>, target: Rect<T>, fit: Fit) -> FitRect<T> {
+        let target_f64 = rect_to_f64(target);
+
+        //Also rejects NaN coordinates.
+        let valid = |r: Range<f64>| r.start <= r.end;
+        if !valid(target_f64.x) || !valid(target_f64.y) {
+            return FitRect {
+                points: Vec::new().into_iter(),
+                affine: Affine::identity(),
+                rect: None,
+            };
+        }
+
+        let (affine, rect) = match bounding(&points) {
+            Some(source) => {
+                let affine = fit_affine(source, target_f64, fit);
+                let rect = clamp_rect(affine.apply_rect(source), target_f64);
+                (affine, rect.cast_coord())
+            }
+            None => (Affine::identity(), target),
+        };
+
+        //Rounding errors could otherwise push points just outside of the target.
+        let points: Vec<[T; 2]> = points
+            .into_iter()
+            .map(|p| {
+                let [x, y] = affine.apply(p);
+                point_from_f64([
+                    x.clamp(target_f64.x.start, target_f64.x.end),
+                    y.clamp(target_f64.y.start, target_f64.y.end),
+                ])
+            })
+            .collect();
+
+        FitRect {
+            points: points.into_iter(),
+            affine,
+            rect: Some(rect),
+        }
+    }
+
+    ///The transform that was applied to the source points.
+    pub fn affine(&self) -> Affine {
+        self.affine
+    }
+}
+
+fn bounding(points: &[[f64; 2]]) -> Option<Rect<f64>> {
+    let (&[x, y], rest) = points.split_first()?;
+    let mut rect = Rect::new(x, x, y, y);
+    for &[x, y] in rest {
+        rect.x.start = rect.x.start.min(x);
+        rect.x.end = rect.x.end.max(x);
+        rect.y.start = rect.y.start.min(y);
+        rect.y.end = rect.y.end.max(y);
+    }
+    Some(rect)
+}
+
+fn clamp_rect(rect: Rect<f64>, bound: Rect<f64>) -> Rect<f64> {
+    Rect::new(
+        rect.x.start.max(bound.x.start),
+        rect.x.end.min(bound.x.end),
+        rect.y.start.max(bound.y.start),
+        rect.y.end.min(bound.y.end),
+    )
+}
+
+///Find the transform that maps the source rect into the target rect.
+///If the source has no width or height, it is centered along that axis.
+fn fit_affine(source: Rect<f64>, target: Rect<f64>, fit: Fit) -> Affine {
+    let center = |r: Rect<f64>| [(r.x.start + r.x.end) / 2.0, (r.y.start + r.y.end) / 2.0];
+    let ratio = |s: f64, t: f64| if s > 0.0 { Some(t / s) } else { None };
+
+    let sx = ratio(source.x.end - source.x.start, target.x.end - target.x.start);
+    let sy = ratio(source.y.end - source.y.start, target.y.end - target.y.start);
+
+    let scale = match fit {
+        Fit::Stretch => [sx.unwrap_or(1.0), sy.unwrap_or(1.0)],
+        Fit::Contain => {
+            let s = match (sx, sy) {
+                (Some(sx), Some(sy)) => sx.min(sy),
+                (Some(s), None) | (None, Some(s)) => s,
+                (None, None) => 1.0,
+            };
+            [s, s]
+        }
+    };
+
+    let [cx, cy] = center(source);
+    Affine::translate([-cx, -cy])
+        .then(Affine::scale(scale))
+        .then(Affine::translate(center(target)))
+}
+
+impl<T: Coord> Iterator for FitRect<T> {
+    type Item = [T; 2];
+    fn next(&mut self) -> Option<[T; 2]> {
+        self.points.next()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.points.size_hint()
+    }
+}
+
+impl<T: Coord> DoubleEndedIterator for FitRect<T> {
+    fn next_back(&mut self) -> Option<[T; 2]> {
+        self.points.next_back()
+    }
+}
+
+impl<T: Coord> ExactSizeIterator for FitRect<T> {}
+
+impl<T: Coord> FusedIterator for FitRect<T> {}
+
+impl<T: Coord> Dist for FitRect<T> {
+    type Num = T;
+    fn exact_len(&self) -> Option<usize> {
+        Some(self.points.len())
+    }
+    fn bounding_rect(&self) -> Option<Rect<T>> {
+        self.rect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    fn close(a: [f64; 2], b: [f64; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-9 && (a[1] - b[1]).abs() < 1e-9
+    }
+
+    fn samples() -> [Affine; 4] {
+        [
+            Affine::rotate_about([1.0, 2.0], 0.7),
+            Affine::scale_about([-3.0, 1.0], [2.0, 0.5]),
+            Affine::shear([0.3, -1.2]),
+            Affine::mirror([4.0, -1.0], 1.1).then(Affine::translate([2.0, 5.0])),
+        ]
+    }
+
+    #[test]
+    fn then_applies_in_order() {
+        let p = [1.5, -2.5];
+        for a in samples() {
+            for b in samples() {
+                assert!(close(a.then(b).apply(p), b.apply(a.apply(p))));
+            }
+        }
+
+        //Translating then rotating is not the same as rotating then translating.
+        let t = Affine::translate([1.0, 0.0]);
+        let r = Affine::rotate(core::f64::consts::FRAC_PI_2);
+        assert!(close(t.then(r).apply([0.0, 0.0]), [0.0, 1.0]));
+        assert!(close(r.then(t).apply([0.0, 0.0]), [1.0, 0.0]));
+    }
+
+    #[test]
+    fn inverse_round_trips() {
+        let p = [1.5, -2.5];
+        for a in samples() {
+            let inv = a.inverse().unwrap();
+            assert!(close(inv.apply(a.apply(p)), p));
+            assert!(close(a.apply(inv.apply(p)), p));
+        }
+        assert_eq!(Affine::scale([1.0, 0.0]).inverse(), None);
+    }
+
+    #[test]
+    fn mirror_and_about() {
+        let m = Affine::mirror([0.0, 2.0], 0.0);
+        assert!(close(m.apply([3.0, 5.0]), [3.0, -1.0]));
+        assert!(close(m.then(m).apply([3.0, 5.0]), [3.0, 5.0]));
+
+        //A vertical mirror flips the x coordinates instead.
+        let m = Affine::mirror([1.0, 0.0], core::f64::consts::FRAC_PI_2);
+        assert!(close(m.apply([3.0, 5.0]), [-1.0, 5.0]));
+
+        //The center of rotation and scaling does not move.
+        assert!(close(
+            Affine::rotate_about([1.0, 2.0], 0.7).apply([1.0, 2.0]),
+            [1.0, 2.0]
+        ));
+        assert!(close(
+            Affine::scale_about([1.0, 2.0], [3.0, 4.0]).apply([2.0, 3.0]),
+            [4.0, 6.0]
+        ));
+    }
+
+    #[test]
+    fn transform_keeps_len_and_order() {
+        let rect = Rect::new(0.0f64, 10.0, 0.0, 10.0);
+        let it = crate::grid_rect_iter(20, rect).translate([5.0, -5.0]);
+        assert_eq!(it.len(), 20);
+        assert_eq!(it.exact_len(), Some(20));
+        assert_eq!(it.bounding_rect(), Some(Rect::new(5.0, 15.0, -5.0, 5.0)));
+
+        let forward: Vec<[f64; 2]> = it.clone().collect();
+        let mut backward: Vec<[f64; 2]> = it.rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        let expected: Vec<[f64; 2]> = crate::grid_rect_iter(20, rect)
+            .map(|[x, y]: [f64; 2]| [x + 5.0, y - 5.0])
+            .collect();
+        assert_eq!(forward, expected);
+    }
+
+    #[test]
+    fn transform_vec2_distributions() {
+        let grid = Grid::new(Rect::new(0.0, 10.0, 0.0, 10.0), 16);
+        let it = grid.clone().scale_about([0.0, 0.0], [2.0, 2.0]);
+        assert_eq!(it.len(), 16);
+        let points: Vec<Vec2<f32>> = it.collect();
+        let expected: Vec<Vec2<f32>> = grid.map(|v| v * 2.0).collect();
+        assert_eq!(points, expected);
+    }
+
+    fn extent<T: Coord>(points: &[[T; 2]]) -> Rect<f64> {
+        bounding(&points.iter().map(|&p| point_to_f64(p)).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn source() -> Vec<[f64; 2]> {
+        //Twice as wide as it is tall.
+        crate::grid_rect_iter(50, Rect::new(-2.0, 2.0, 1.0, 3.0))
+            .chain([[2.0, 3.0]])
+            .collect()
+    }
+
+    #[test]
+    fn fit_contain_keeps_aspect_ratio() {
+        let target = Rect::new(0.0, 10.0, 0.0, 10.0);
+        let points: Vec<[f64; 2]> = source()
+            .into_iter()
+            .fit_rect(100, target, Fit::Contain)
+            .collect();
+        let r = extent(&points);
+        assert!(close([r.x.start, r.x.end], [0.0, 10.0]));
+        assert!(close([r.y.start, r.y.end], [2.5, 7.5]));
+    }
+
+    #[test]
+    fn fit_stretch_touches_every_side() {
+        let target = Rect::new(0.0, 10.0, -5.0, 15.0);
+        let points: Vec<[f64; 2]> = source()
+            .into_iter()
+            .fit_rect(100, target, Fit::Stretch)
+            .collect();
+        let r = extent(&points);
+        assert!(close([r.x.start, r.x.end], [0.0, 10.0]));
+        assert!(close([r.y.start, r.y.end], [-5.0, 15.0]));
+    }
+
+    #[test]
+    fn fit_centers_zero_width_source() {
+        let line = [[3.0, 0.0], [3.0, 1.0], [3.0, 4.0]];
+        let target = Rect::new(0.0, 10.0, 0.0, 20.0);
+        for fit in [Fit::Contain, Fit::Stretch] {
+            let points: Vec<[f64; 2]> = line.into_iter().fit_rect(3, target, fit).collect();
+            let r = extent(&points);
+            assert!(close([r.x.start, r.x.end], [5.0, 5.0]));
+        }
+        let points: Vec<[f64; 2]> = line.into_iter().fit_rect(3, target, Fit::Contain).collect();
+        assert_eq!(points, [[5.0, 0.0], [5.0, 5.0], [5.0, 20.0]]);
+    }
+
+    #[test]
+    fn fit_affine_reproduces_points() {
+        let source = source();
+        for fit in [Fit::Contain, Fit::Stretch] {
+            let target = Rect::new(-1.0, 7.0, 2.0, 3.0);
+            let it = source.iter().copied().fit_rect(40, target, fit);
+            assert_eq!(it.len(), 40);
+            let affine = it.affine();
+            for (&p, q) in source.iter().zip(it) {
+                assert!(close(affine.apply(p), q));
+            }
+        }
+    }
+
+    #[test]
+    fn fit_invalid_target() {
+        let target = Rect::new(10.0, 0.0, 0.0, 10.0);
+        let it = source().into_iter().fit_rect(10, target, Fit::Contain);
+        assert_eq!(it.len(), 0);
+        assert_eq!(it.bounding_rect(), None);
+
+        let target = Rect::new(0.0, f64::NAN, 0.0, 10.0);
+        assert_eq!(
+            source()
+                .into_iter()
+                .fit_rect(10, target, Fit::Stretch)
+                .count(),
+            0
+        );
+    }
+}
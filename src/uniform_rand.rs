@@ -7,119 +7,56 @@ use axgeom;
 
 use core::iter::FusedIterator;
 
-/*
-pub struct RangeGenIterf32{
-    max:usize,
-    counter:usize,
-    rng:rand::StdRng,
-    xvaluegen:UniformRangeGenerator,
-    yvaluegen:UniformRangeGenerator,
-    radiusgen:UniformRangeGenerator,
-    velocity_dir:UniformRangeGenerator,
-    velocity_mag:UniformRangeGenerator
-}
-
-pub struct Retf32{
-    pub id:usize,
-    pub pos:[f32;2],
-    pub vel:[f32;2],
-    pub radius:[f32;2],
-}
-
-pub struct RetInteger{
-    pub id:usize,
-    pub pos:[isize;2],
-    pub vel:[isize;2],
-    pub radius:[isize;2],
-}
-impl Retf32{
-    pub fn into_isize(self)->RetInteger{
-        let id=self.id;
-        let pos=[self.pos[0] as isize,self.pos[1] as isize];
-        let vel=[self.vel[0] as isize,self.vel[1] as isize];
-        let radius=[self.radius[0] as isize,self.radius[1] as isize];
-        RetInteger{id,pos,vel,radius}
-    }
-}
-impl std::iter::FusedIterator for RangeGenIterf32{}
-impl ExactSizeIterator for RangeGenIterf32{}
-impl Iterator for RangeGenIterf32{
-    type Item=Retf32;
-    fn size_hint(&self)->(usize,Option<usize>){
-        (self.max,Some(self.max))
-    }
-    fn next(&mut self)->Option<Self::Item>{
-
-        if self.counter==self.max{
-            return None
-        }
-
-        let rng=&mut self.rng;
-        let px=self.xvaluegen.get(rng) as f32;
-        let py=self.yvaluegen.get(rng) as f32;
-        let rx=self.radiusgen.get(rng) as f32;
-        let ry=self.radiusgen.get(rng) as f32;
-
-        let (velx,vely)={
-            let vel_dir=self.velocity_dir.get(rng) as f32;
-            let vel_dir=vel_dir.to_radians();
-            let (mut xval,mut yval)=(vel_dir.cos(),vel_dir.sin());
-            let vel_mag=self.velocity_mag.get(rng) as f32;
-            xval*=vel_mag;
-            yval*=vel_mag;
-            (xval,yval)
-        };
-
-        let curr=self.counter;
-        self.counter+=1;
-        let r=Retf32{id:curr,pos:[px,py],vel:[velx,vely],radius:[rx,ry]};
-        Some(r)
-    }
-}
-pub fn create_world_generator(num:usize,area:&[isize;4],radius:[isize;2],velocity:[isize;2])->RangeGenIterf32{
-    let arr:&[usize]=&[100,42,6];
-    let rng =  SeedableRng::from_seed(arr);
-
-
-    let xvaluegen=UniformRangeGenerator::new(area[0],area[1]);
-    let yvaluegen=UniformRangeGenerator::new(area[2],area[3]);
-    let radiusgen= UniformRangeGenerator::new(radius[0],radius[1]);
-
-
-    let velocity_dir=UniformRangeGenerator::new(0,360);
-    let velocity_mag= UniformRangeGenerator::new(velocity[0],velocity[1]);
-
-    RangeGenIterf32{max:num,counter:0,rng,xvaluegen,yvaluegen,radiusgen,velocity_dir,velocity_mag}
-}
-*/
-
 use rand::prelude::*;
 
+use crate::coord::CastCoord;
+use crate::seeded_rng;
+use crate::unit_f32;
+use crate::Dist;
 use crate::RadiusGen;
 use crate::RadiusGenInt;
+use crate::SeededRng;
 
-pub struct UniformRandGen {
+#[derive(Clone)]
+pub struct UniformRandGen<R = ThreadRng> {
     area: Rect<f32>,
-    rng: ThreadRng,
+    rng: R,
 }
 
 impl UniformRandGen {
     #[deprecated(since = "0.3.1", note = "use rand_iter() instead")]
     pub fn new(area: Rect<f32>) -> UniformRandGen {
-        let rng = rand::thread_rng();
-        UniformRandGen { area, rng }
+        UniformRandGen::with_rng(area, rand::thread_rng())
     }
     #[deprecated(since = "0.3.1", note = "use rand_iter() instead")]
     pub fn with_radius(self, min: f32, max: f32) -> core::iter::Zip<UniformRandGen, RadiusGen> {
         self.zip(RadiusGen::new(vec2(min, min), vec2(max, max)))
     }
+}
+
+impl UniformRandGen<SeededRng> {
+    pub fn with_seed(area: Rect<f32>, seed: u64) -> Self {
+        UniformRandGen::with_rng(area, seeded_rng(seed))
+    }
+}
+
+impl<R: RngCore> UniformRandGen<R> {
+    pub fn with_rng(area: Rect<f32>, rng: R) -> Self {
+        UniformRandGen { area, rng }
+    }
 
-    pub fn with_int(self) -> UniformRandGenInt {
+    pub fn with_int(self) -> UniformRandGenInt<R> {
         UniformRandGenInt(self)
     }
 }
 
-pub struct UniformRandGenInt(UniformRandGen);
+///A wrapper around a UniformRandGen that produces integers.
+///
+///The points are rounded down like every other [`crate::coord::Coord`] conversion, so they stay
+///inside of the area. Before 0.5 they were truncated towards zero, so in areas
+///with negative coordinates points are now one further from the origin.
+#[derive(Clone)]
+pub struct UniformRandGenInt<R = ThreadRng>(UniformRandGen<R>);
 impl UniformRandGenInt {
     #[deprecated(since = "0.3.1", note = "use rand_iter() instead")]
     pub fn with_radius(
@@ -131,45 +68,43 @@ impl UniformRandGenInt {
     }
 }
 
-impl Iterator for UniformRandGen {
+impl<R: RngCore> Iterator for UniformRandGen<R> {
     type Item = Vec2<f32>;
     fn next(&mut self) -> Option<Vec2<f32>> {
         let rng = &mut self.rng;
         let area = &self.area;
-        let x: f32 = rng.gen::<f32>() * (area.x.end - area.x.start); // generates a float between 0 and 1
-        let y: f32 = rng.gen::<f32>() * (area.y.end - area.y.start);
+        let x: f32 = area.x.start + unit_f32(rng) * (area.x.end - area.x.start); // generates a float between 0 and 1
+        let y: f32 = area.y.start + unit_f32(rng) * (area.y.end - area.y.start);
         Some(vec2(x, y))
     }
 }
-impl FusedIterator for UniformRandGen {}
+impl<R: RngCore> FusedIterator for UniformRandGen<R> {}
 
-//impl Dist<f32> for UniformRandGen{}
+impl<R: RngCore> Dist for UniformRandGen<R> {
+    type Num = f32;
+    fn exact_len(&self) -> Option<usize> {
+        None
+    }
+    fn bounding_rect(&self) -> Option<Rect<f32>> {
+        Some(self.area)
+    }
+}
 
-impl Iterator for UniformRandGenInt {
+impl<R: RngCore> Iterator for UniformRandGenInt<R> {
     type Item = Vec2<i32>;
     fn next(&mut self) -> Option<Vec2<i32>> {
-        self.0.next().map(|a| vec2(a.x as i32, a.y as i32))
+        self.0.next().map(CastCoord::<i32>::cast_coord)
     }
 }
-impl FusedIterator for UniformRandGenInt {}
-
-//impl Dist<i32> for UniformRandGenInt{}
+impl<R: RngCore> FusedIterator for UniformRandGenInt<R> {}
 
-/*
-
-struct UniformRangeGenerator{
-    range:Range<isize>
-}
-
-impl UniformRangeGenerator{
-    pub fn new(a:isize,b:isize)->Self{
-        //let rr = a.get_range2::<axgeom::XAXISS>();
-        let xdist = rand::distributions::Range::new(a,b);
-        UniformRangeGenerator{range:xdist}
+impl<R: RngCore> Dist for UniformRandGenInt<R> {
+    type Num = i32;
+    fn exact_len(&self) -> Option<usize> {
+        None
     }
-    pub fn get(&self,rng:&mut StdRng)->isize{
-        self.range.ind_sample(rng)
+    fn bounding_rect(&self) -> Option<Rect<i32>> {
+        //Rounding down never moves a point outside of the rounded down area.
+        Some(self.0.area.cast_coord())
     }
 }
-
-*/
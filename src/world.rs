@@ -0,0 +1,229 @@
+use core::iter::FusedIterator;
+use rand::RngCore;
+
+use crate::aabb::Point2;
+use crate::coord::*;
+use crate::seeded_rng;
+use crate::unit_f32;
+use crate::SeededRng;
+use axgeom::Rect;
+use axgeom::Vec2;
+
+///The full starting state of a bot.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Retf32 {
+    pub id: usize,
+    pub pos: [f32; 2],
+    pub vel: [f32; 2],
+    pub radius: [f32; 2],
+}
+
+///The full starting state of a bot with integer coordinates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetInteger {
+    pub id: usize,
+    pub pos: [isize; 2],
+    pub vel: [isize; 2],
+    pub radius: [isize; 2],
+}
+
+impl Retf32 {
+    ///Convert to integer coordinates. The position and radius are rounded down,
+    ///while the velocity is truncated towards zero so that it has no bias in any direction.
+    pub fn into_isize(self) -> RetInteger {
+        let id = self.id;
+        let pos = self.pos.cast_coord();
+        let vel = self.vel.map(|v| v as isize);
+        let radius = self.radius.cast_coord();
+        RetInteger {
+            id,
+            pos,
+            vel,
+            radius,
+        }
+    }
+}
+
+///Generates bots with an id, a position, a velocity and a radius.
+///
+///The positions are taken from any distribution, with any coordinate type.
+///Each axis of the radius is drawn uniformly from the `radius` range.
+///The velocity has a uniformly random direction and a magnitude
+///drawn uniformly from the `velocity` range.
+///Ids count up from zero.
+#[derive(Clone)]
+pub struct WorldGen<I, R = SeededRng> {
+    positions: I,
+    counter: usize,
+    radius: [f32; 2],
+    velocity: [f32; 2],
+    rng: R,
+}
+
+impl<I: Iterator> WorldGen<I, SeededRng>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+    pub fn with_seed(positions: I, radius: [f32; 2], velocity: [f32; 2], seed: u64) -> Self {
+        WorldGen::with_rng(positions, radius, velocity, seeded_rng(seed))
+    }
+}
+
+impl<I: Iterator, R: RngCore> WorldGen<I, R>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+    pub fn with_rng(positions: I, radius: [f32; 2], velocity: [f32; 2], rng: R) -> Self {
+        WorldGen {
+            positions,
+            counter: 0,
+            radius,
+            velocity,
+            rng,
+        }
+    }
+
+    ///Convert every bot to integer coordinates.
+    pub fn into_isize(self) -> core::iter::Map<Self, fn(Retf32) -> RetInteger> {
+        self.map(Retf32::into_isize)
+    }
+}
+
+impl<I: Iterator, R: RngCore> Iterator for WorldGen<I, R>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+    type Item = Retf32;
+    fn next(&mut self) -> Option<Retf32> {
+        let pos: Vec2<f32> = self.positions.next()?.to_vec2().cast_coord();
+
+        let rng = &mut self.rng;
+        let [rmin, rmax] = self.radius;
+        let rx = rmin + unit_f32(rng) * (rmax - rmin);
+        let ry = rmin + unit_f32(rng) * (rmax - rmin);
+
+        let vel = {
+            let vel_dir = unit_f32(rng) * core::f32::consts::TAU;
+            let [vmin, vmax] = self.velocity;
+            let vel_mag = vmin + unit_f32(rng) * (vmax - vmin);
+            [vel_dir.cos() * vel_mag, vel_dir.sin() * vel_mag]
+        };
+
+        let id = self.counter;
+        self.counter += 1;
+        Some(Retf32 {
+            id,
+            pos: [pos.x, pos.y],
+            vel,
+            radius: [rx, ry],
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+impl<I: FusedIterator, R: RngCore> FusedIterator for WorldGen<I, R>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+}
+
+impl<I: ExactSizeIterator, R: RngCore> ExactSizeIterator for WorldGen<I, R>
+where
+    I::Item: Point2,
+    <I::Item as Point2>::Num: Coord,
+{
+}
+
+///Generates `num` bots uniformly distributed over the area.
+///The same seed always produces the same bots.
+pub fn create_world_generator(
+    num: usize,
+    area: Rect<f32>,
+    radius: [f32; 2],
+    velocity: [f32; 2],
+    seed: u64,
+) -> impl FusedIterator<Item = Retf32> + Clone {
+    let mut rng = seeded_rng(seed);
+    let positions = crate::rand2_iter_seeded(area, rng.next_u64()).take(num);
+    WorldGen::with_rng(positions, radius, velocity, rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axgeom::rect;
+
+    #[test]
+    fn create_world_generator_golden() {
+        let bots: Vec<Retf32> =
+            create_world_generator(2, rect(0.0, 10.0, 0.0, 10.0), [1.0, 2.0], [0.0, 1.0], 5)
+                .collect();
+        assert_eq!(
+            bots,
+            [
+                Retf32 {
+                    id: 0,
+                    pos: [2.5515409, 6.837284],
+                    vel: [-0.120673425, -0.21825647],
+                    radius: [1.0066578, 1.1156218],
+                },
+                Retf32 {
+                    id: 1,
+                    pos: [1.9470918, 2.0192795],
+                    vel: [0.014661154, -0.010623557],
+                    radius: [1.2699676, 1.1540465],
+                },
+            ]
+        );
+
+        let bots: Vec<RetInteger> = bots.into_iter().map(Retf32::into_isize).collect();
+        assert_eq!(
+            bots,
+            [
+                RetInteger {
+                    id: 0,
+                    pos: [2, 6],
+                    vel: [0, 0],
+                    radius: [1, 1],
+                },
+                RetInteger {
+                    id: 1,
+                    pos: [1, 2],
+                    vel: [0, 0],
+                    radius: [1, 1],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn into_isize_truncates_velocity() {
+        let bots: Vec<RetInteger> = WorldGen::with_seed(
+            crate::grid_rect_iter(3, rect(0, 10, 0, 10)),
+            [1.0, 2.0],
+            [0.0, 1.0],
+            5,
+        )
+        .into_isize()
+        .collect();
+        assert!(bots.iter().all(|b| b.vel == [0, 0]));
+        assert_eq!(
+            bots.iter().map(|b| b.pos).collect::<Vec<_>>(),
+            [[0, 0], [5, 0], [0, 5]]
+        );
+
+        let bot = Retf32 {
+            id: 0,
+            pos: [0.5, 0.5],
+            vel: [-1.5, 1.5],
+            radius: [1.0, 1.0],
+        };
+        assert_eq!(bot.into_isize().vel, [-1, 1]);
+    }
+}